@@ -1,5 +1,5 @@
 use criterion::{Criterion, criterion_group, criterion_main};
-use find_identical_files::{FIFResult, FileInfo, Key};
+use find_identical_files::{FIFResult, FileInfo, Key, file_id};
 use jwalk::{DirEntry, Parallelism, WalkDirGeneric};
 
 /// Updated Type Alias: The client state now holds a Result to capture potential
@@ -27,6 +27,7 @@ pub fn process_dir_entries_v1(dir_entry_results: &mut JwalkResults, min_size: u6
                     let result = Key::new(file_size, None).map(|key| FileInfo {
                         key,
                         path: dir_entry.path(),
+                        file_id: file_id(&metadata),
                     });
                     dir_entry.client_state = Some(result);
                 }
@@ -47,6 +48,7 @@ pub fn process_dir_entries_v2(dir_entry_results: &mut JwalkResults, min_size: u6
                 let result = Key::new(file_size, None).map(|key| FileInfo {
                     key,
                     path: dir_entry.path(),
+                    file_id: file_id(&metadata),
                 });
                 dir_entry.client_state = Some(result);
             }
@@ -65,16 +67,17 @@ pub fn process_dir_entries_v3(dir_entry_results: &mut JwalkResults, min_size: u6
                 dir_entry
                     .metadata()
                     .ok()
-                    .map(|metadata| (dir_entry, metadata.len()))
+                    .map(|metadata| (dir_entry, metadata.len(), file_id(&metadata)))
             } else {
                 None
             }
         })
-        .filter(|(_, file_size)| *file_size >= min_size && *file_size <= max_size)
-        .for_each(|(dir_entry, file_size)| {
+        .filter(|(_, file_size, _)| *file_size >= min_size && *file_size <= max_size)
+        .for_each(|(dir_entry, file_size, file_id)| {
             let result = Key::new(file_size, None).map(|key| FileInfo {
                 key,
                 path: dir_entry.path(),
+                file_id,
             });
             dir_entry.client_state = Some(result);
         });
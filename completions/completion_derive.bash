@@ -23,18 +23,26 @@ _find-identical-files() {
 
     case "${cmd}" in
         find__identical__files)
-            opts="-a -b -B -c -d -D -e -f -F -g -i -o -r -s -t -v -w -x -h -V --algorithm --min_size --max_size --csv_dir --min_depth --max_depth --extended_path --min_frequency --max_frequency --generate --input_dir --omit_hidden --result_format --sort --time --verbose --wipe_terminal --xlsx_dir --help --version"
+            opts="-a -A -b -B -c -d -D -e -E -f -F -g -H -i -j -I -k -0 -o -p -q -u -P -r -R -s -t -v -w -x -h -V --algorithm --action --min_size --max_size --csv_dir --min_depth --max_depth --extended_path --execute --extension --exclude_extension --exclude --min_frequency --max_frequency --files_from --first_bytes --follow_symlinks --generate --gitignore --human_readable --input_dir --ignore_file --include --journal --interactive --keep --last_bytes --lockstep --newer_than --null --one_file_system --older_than --omit_hidden --priority_dir --progressive --quarantine_dir --quick --undo --prompt --result_format --restore --reference_dir --sort --symlink_type --time --verbose --verify --wipe_terminal --xlsx_dir --help --version"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
                 --algorithm)
-                    COMPREPLY=($(compgen -W "ahash blake3 foldhash fxhash sha256 sha512" -- "${cur}"))
+                    COMPREPLY=($(compgen -W "ahash blake3 crc32c foldhash fxhash md5 sha256 sha512 xxh3 xxh128" -- "${cur}"))
                     return 0
                     ;;
                 -a)
-                    COMPREPLY=($(compgen -W "ahash blake3 foldhash fxhash sha256 sha512" -- "${cur}"))
+                    COMPREPLY=($(compgen -W "ahash blake3 crc32c foldhash fxhash md5 sha256 sha512 xxh3 xxh128" -- "${cur}"))
+                    return 0
+                    ;;
+                --action)
+                    COMPREPLY=($(compgen -W "delete hard-link reflink symlink quarantine" -- "${cur}"))
+                    return 0
+                    ;;
+                -A)
+                    COMPREPLY=($(compgen -W "delete hard-link reflink symlink quarantine" -- "${cur}"))
                     return 0
                     ;;
                 --min_size)
@@ -77,6 +85,18 @@ _find-identical-files() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --extension)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --exclude_extension)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --exclude)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --min_frequency)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -93,6 +113,14 @@ _find-identical-files() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --files_from)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --first_bytes)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --generate)
                     COMPREPLY=($(compgen -W "bash elvish fish powershell zsh" -- "${cur}"))
                     return 0
@@ -109,12 +137,96 @@ _find-identical-files() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --ignore_file)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --include)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --journal)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -j)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --keep)
+                    COMPREPLY=($(compgen -W "oldest newest shortest-path longest-path first-priority last-priority alphabetical" -- "${cur}"))
+                    return 0
+                    ;;
+                -k)
+                    COMPREPLY=($(compgen -W "oldest newest shortest-path longest-path first-priority last-priority alphabetical" -- "${cur}"))
+                    return 0
+                    ;;
+                --last_bytes)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --newer_than)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --older_than)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --priority_dir)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -p)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --quarantine_dir)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -q)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --quick)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --undo)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -u)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --result_format)
-                    COMPREPLY=($(compgen -W "json yaml personal" -- "${cur}"))
+                    COMPREPLY=($(compgen -W "json yaml personal shell" -- "${cur}"))
                     return 0
                     ;;
                 -r)
-                    COMPREPLY=($(compgen -W "json yaml personal" -- "${cur}"))
+                    COMPREPLY=($(compgen -W "json yaml personal shell" -- "${cur}"))
+                    return 0
+                    ;;
+                --restore)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -R)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --reference_dir)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --symlink_type)
+                    COMPREPLY=($(compgen -W "relative absolute" -- "${cur}"))
+                    return 0
+                    ;;
+                --verify)
+                    COMPREPLY=($(compgen -W "auto always never" -- "${cur}"))
                     return 0
                     ;;
                 --xlsx_dir)
@@ -1,28 +1,77 @@
 complete -c find-identical-files -s a -l algorithm -d 'Choose the hash algorithm' -r -f -a "ahash\t''
 blake3\t''
+crc32c\t''
 foldhash\t''
 fxhash\t''
+md5\t''
 sha256\t''
-sha512\t''"
+sha512\t''
+xxh3\t''
+xxh128\t''"
+complete -c find-identical-files -s A -l action -d 'Choose an action to apply to the identical files' -r -f -a "delete\t'Remove the duplicates, keeping only one file per group'
+hard-link\t'Replace the duplicates with hard links to the file kept'
+reflink\t'Share the data of the duplicates with the file kept (copy-on-write clones)'
+symlink\t'Replace the duplicates with symbolic links to the file kept (see --symlink_type)'
+quarantine\t'Move the duplicates into the quarantine directory (see --quarantine_dir)'"
 complete -c find-identical-files -s b -l min_size -d 'Set a minimum file size (in bytes) to search for identical files' -r
 complete -c find-identical-files -s B -l max_size -d 'Set a maximum file size (in bytes) to search for identical files' -r
 complete -c find-identical-files -s c -l csv_dir -d 'Set the output directory for the CSV file (fif.csv)' -r -F
 complete -c find-identical-files -s d -l min_depth -d 'Set the minimum depth to search for identical files' -r
 complete -c find-identical-files -s D -l max_depth -d 'Set the maximum depth to search for identical files' -r
+complete -c find-identical-files -l extension -d 'Search only the files with these extensions (case insensitive)' -r
+complete -c find-identical-files -l exclude_extension -d 'Skip the files with these extensions (case insensitive)' -r
+complete -c find-identical-files -l exclude -d 'Exclude the files and directories matching the glob pattern' -r
 complete -c find-identical-files -s f -l min_frequency -d 'Minimum frequency (number of identical files) to be filtered' -r
 complete -c find-identical-files -s F -l max_frequency -d 'Maximum frequency (number of identical files) to be filtered' -r
+complete -c find-identical-files -l files_from -d 'Read the files to compare from this list, instead of walking the input directories' -r -F
+complete -c find-identical-files -l first_bytes -d 'Set the number of initial bytes hashed to filter out the files that differ early' -r
 complete -c find-identical-files -s g -l generate -d 'If provided, outputs the completion file for given shell' -r -f -a "bash\t''
 elvish\t''
 fish\t''
 powershell\t''
 zsh\t''"
-complete -c find-identical-files -s i -l input_dir -d 'Set the input directory where to search for identical files [default: current directory]' -r -F
+complete -c find-identical-files -s i -l input_dir -d 'Set the input directories where to search for identical files [default: current directory]' -r -F
+complete -c find-identical-files -l ignore_file -d 'Skip the files listed in the ignore file (gitignore syntax)' -r -F
+complete -c find-identical-files -l include -d 'Search only the files matching the glob pattern, or inside a matching directory' -r
+complete -c find-identical-files -s j -l journal -d 'Set the journal file where every step of an action is recorded (JSON Lines)' -r -F
+complete -c find-identical-files -s k -l keep -d 'Choose the rule used to select the file to keep in each group' -r -f -a "oldest\t'Keep the file with the oldest modification time'
+newest\t'Keep the file with the newest modification time'
+shortest-path\t'Keep the file with the shortest path'
+longest-path\t'Keep the file with the longest path'
+first-priority\t'Keep the file found in the first directory of the priority list'
+last-priority\t'Keep the file found in the last directory of the priority list'
+alphabetical\t'Keep the first file in alphabetical order'"
+complete -c find-identical-files -l last_bytes -d 'Set the number of final bytes hashed to filter out the files that differ at the end' -r
+complete -c find-identical-files -l newer_than -d 'Search only the files modified after this time' -r
+complete -c find-identical-files -l older_than -d 'Search only the files modified before this time' -r
+complete -c find-identical-files -s p -l priority_dir -d 'Add a directory to the priority list used by the keep rules first-priority and last-priority' -r -F
+complete -c find-identical-files -s q -l quarantine_dir -d 'Set the quarantine directory used by the quarantine action' -r -F
+complete -c find-identical-files -l quick -d 'Quick mode: hash N evenly spaced blocks, plus the head and the tail, instead of the entire files' -r
+complete -c find-identical-files -s u -l undo -d 'Undo the steps recorded in the given journal, in reverse order' -r -F
 complete -c find-identical-files -s r -l result_format -d 'Print the result in the chosen format' -r -f -a "json\t''
 yaml\t''
-personal\t''"
+personal\t''
+shell\t'POSIX shell script with one command per duplicate (see --action), to review before running'"
+complete -c find-identical-files -s R -l restore -d 'Restore the files moved to quarantine, reading the given manifest (fif_manifest.json)' -r -F
+complete -c find-identical-files -l reference_dir -d 'Add reference directories, searched along with the input directories' -r -F
+complete -c find-identical-files -l symlink_type -d 'Choose the kind of symbolic link created by the symlink action' -r -f -a "relative\t'Target relative to the directory of the link (e.g. ../photos/img.jpg)'
+absolute\t'Canonical absolute target (e.g. /home/user/photos/img.jpg)'"
+complete -c find-identical-files -l verify -d 'Compare the identical files byte by byte, after the hash' -r -f -a "auto\t'Verify only when files may be changed (see --action, --interactive, --prompt and shell scripts)'
+always\t'Always verify, also when only reporting'
+never\t'Never verify: trust the hash'"
 complete -c find-identical-files -s x -l xlsx_dir -d 'Set the output directory for the XLSX file (fif.xlsx)' -r -F
 complete -c find-identical-files -s e -l extended_path -d 'Prints extended path of identical files, otherwise relative path'
+complete -c find-identical-files -s E -l execute -d 'Execute the chosen action, otherwise only print the dry-run preview'
+complete -c find-identical-files -l follow_symlinks -d 'Follow symbolic links to files and directories'
+complete -c find-identical-files -l gitignore -d 'Skip the files ignored by .gitignore, .ignore, .git/info/exclude and the global git excludes'
+complete -c find-identical-files -s H -l human_readable -d 'Show sizes in human units (e.g. 3.2 GiB) instead of bytes with thousands separators'
+complete -c find-identical-files -s I -l interactive -d 'Review the identical files in an interactive terminal interface'
+complete -c find-identical-files -l lockstep -d 'Compare the files of each group block by block at the same time, instead of hashing each file'
+complete -c find-identical-files -s 0 -l null -d 'Paths of --files_from are separated by NUL bytes (find -print0, git ls-files -z)'
+complete -c find-identical-files -l one_file_system -d 'Stay on the file system of each input directory: do not descend into mount points'
 complete -c find-identical-files -s o -l omit_hidden -d 'Omit hidden files (starts with \'.\'), otherwise search all files'
+complete -c find-identical-files -l progressive -d 'Hash prefixes of growing size (4 KiB, 64 KiB, 1 MiB, 16 MiB, ...) before the entire files'
+complete -c find-identical-files -s P -l prompt -d 'Review the identical files one group at a time, with a line-based prompt'
 complete -c find-identical-files -s s -l sort -d 'Sort result by number of identical files, otherwise sort by file size'
 complete -c find-identical-files -s t -l time -d 'Show total execution time'
 complete -c find-identical-files -s v -l verbose -d 'Show intermediate runtime messages'
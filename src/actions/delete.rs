@@ -0,0 +1,14 @@
+use crate::{FIFError, FIFResult};
+use std::{fs, path::Path};
+
+use super::Outcome;
+
+/// Removes a duplicate file.
+pub fn remove_duplicate(duplicate: &Path) -> FIFResult<Outcome> {
+    fs::remove_file(duplicate).map_err(|io_error| FIFError::FileActionError {
+        path: duplicate.to_path_buf(),
+        io_error,
+    })?;
+
+    Ok(Outcome::Done)
+}
@@ -0,0 +1,45 @@
+use crate::{FIFError, FIFResult, get_metadata};
+use std::{fs, path::Path};
+
+use super::{Outcome, device_id, file_id, temporary_path};
+
+/// Replaces a duplicate with a hard link to the keeper.
+///
+/// The link is first created under a temporary name in the directory of the
+/// duplicate and then atomically renamed over it, so the duplicate path is
+/// never missing, even if the process is interrupted.
+///
+/// Hard links cannot cross file systems: duplicates stored on another device
+/// than the keeper are skipped.
+pub fn link_duplicate(keeper: &Path, duplicate: &Path) -> FIFResult<Outcome> {
+    let keeper_metadata = get_metadata(&keeper)?;
+    let duplicate_metadata = get_metadata(&duplicate)?;
+
+    if device_id(&keeper_metadata) != device_id(&duplicate_metadata) {
+        return Ok(Outcome::Skipped("different file systems".to_string()));
+    }
+
+    if file_id(&keeper_metadata).is_some()
+        && file_id(&keeper_metadata) == file_id(&duplicate_metadata)
+    {
+        return Ok(Outcome::Skipped("already hard linked".to_string()));
+    }
+
+    let temporary = temporary_path(duplicate);
+
+    fs::hard_link(keeper, &temporary).map_err(|io_error| FIFError::FileActionError {
+        path: temporary.clone(),
+        io_error,
+    })?;
+
+    if let Err(io_error) = fs::rename(&temporary, duplicate) {
+        // Do not leave the temporary link behind.
+        let _ = fs::remove_file(&temporary);
+        return Err(FIFError::FileActionError {
+            path: duplicate.to_path_buf(),
+            io_error,
+        });
+    }
+
+    Ok(Outcome::Done)
+}
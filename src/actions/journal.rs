@@ -0,0 +1,394 @@
+use crate::{Action, FIFError, FIFResult, JournalEntry, JournalStatus, open_file, traits::Colors};
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{Outcome, file_id, move_no_clobber, temporary_path};
+
+/// Append-only journal of the steps applied to the duplicates.
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+}
+
+impl Journal {
+    /// Opens (or creates) the journal file in append mode.
+    pub fn open(path: &Path) -> FIFResult<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|io_error| FIFError::FileActionError {
+                path: path.to_path_buf(),
+                io_error,
+            })?;
+
+        Ok(Journal {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// Path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an entry and flushes it to disk before returning.
+    pub fn append(&mut self, entry: &JournalEntry) -> FIFResult<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        self.file
+            .write_all(&line)
+            .and_then(|_| self.file.sync_data())
+            .map_err(|io_error| FIFError::FileActionError {
+                path: self.path.clone(),
+                io_error,
+            })
+    }
+}
+
+/// Seconds since the UNIX epoch.
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Reads the journal and returns one entry per step, with its final status.
+///
+/// A truncated last line (interrupted write) is ignored.
+fn read_journal(journal_path: &Path) -> FIFResult<Vec<JournalEntry>> {
+    let file = open_file(&journal_path)?;
+    let mut steps: Vec<JournalEntry> = Vec::new();
+
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry: JournalEntry = match serde_json::from_str(&line) {
+            Ok(entry) => entry,
+            Err(error) => {
+                eprintln!("{}: {line:?} ({error})", "Skipped".yellow().bold());
+                continue;
+            }
+        };
+
+        match entry.status {
+            JournalStatus::Started => steps.push(entry),
+            status => {
+                // The final status updates the last step started on the same file.
+                if let Some(step) = steps.iter_mut().rev().find(|step| {
+                    step.source == entry.source && step.status == JournalStatus::Started
+                }) {
+                    step.status = status;
+                }
+            }
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Replays the journal in reverse order, undoing every step that changed a file.
+///
+/// Steps of an interrupted run (without a final status) are undone
+/// according to the current state of the files.
+pub fn undo_journal(journal_path: &Path) -> FIFResult<()> {
+    let steps = read_journal(journal_path)?;
+
+    let mut num_restored: usize = 0;
+    let mut num_skipped: usize = 0;
+    let mut num_failed: usize = 0;
+
+    let pending = steps
+        .iter()
+        .rev()
+        .filter(|step| matches!(step.status, JournalStatus::Started | JournalStatus::Done));
+
+    for step in pending {
+        match undo_step(step) {
+            Ok(Outcome::Done) => {
+                println!("Undo {}: {:?}", step.operation, step.source);
+                num_restored += 1;
+            }
+            Ok(Outcome::Skipped(reason)) => {
+                eprintln!(
+                    "{}: {:?} ({reason})",
+                    "Skipped".yellow().bold(),
+                    step.source
+                );
+                num_skipped += 1;
+            }
+            Err(error) => {
+                eprintln!("{error}");
+                num_failed += 1;
+            }
+        }
+    }
+
+    println!("Number of restored files: {num_restored}");
+    println!("Number of skipped files: {num_skipped}");
+    println!("Number of failed files: {num_failed}\n");
+
+    Ok(())
+}
+
+/// Undoes a single step, checking first that it was actually applied.
+fn undo_step(step: &JournalEntry) -> FIFResult<Outcome> {
+    remove_temporary_files(&step.source)?;
+
+    let source = fs::symlink_metadata(&step.source).ok();
+
+    match step.operation {
+        Action::Quarantine => {
+            if source.is_some() {
+                return Ok(Outcome::Skipped("nothing to undo".to_string()));
+            }
+            if !step.target.exists() {
+                return Ok(Outcome::Skipped("not found in quarantine".to_string()));
+            }
+            move_no_clobber(&step.target, &step.source).map_err(|io_error| {
+                FIFError::FileActionError {
+                    path: step.source.clone(),
+                    io_error,
+                }
+            })?;
+            Ok(Outcome::Done)
+        }
+        Action::Reflink => Ok(Outcome::Skipped(
+            "reflinked files are already independent copies".to_string(),
+        )),
+        Action::Delete | Action::HardLink | Action::Symlink => {
+            let applied = match &source {
+                None => true,
+                Some(metadata) if metadata.is_symlink() => step.operation == Action::Symlink,
+                Some(metadata) => {
+                    step.operation == Action::HardLink
+                        && fs::metadata(&step.target)
+                            .is_ok_and(|target| file_id(&target) == file_id(metadata))
+                }
+            };
+
+            if !applied {
+                return Ok(Outcome::Skipped("nothing to undo".to_string()));
+            }
+
+            copy_from_keeper(step)
+        }
+    }
+}
+
+/// Recreates the duplicate as an independent copy of the file kept.
+///
+/// The file kept must still match the size and hash recorded in the journal.
+fn copy_from_keeper(step: &JournalEntry) -> FIFResult<Outcome> {
+    let keeper = &step.target;
+    let metadata = match fs::metadata(keeper) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            return Ok(Outcome::Skipped(
+                "the file kept no longer exists".to_string(),
+            ));
+        }
+    };
+
+    let mut unchanged = metadata.is_file() && metadata.len() == step.key.size as u64;
+    if let (true, Some(hash)) = (unchanged, &step.key.hash) {
+        unchanged = step.algorithm.calculate_hash(open_file(keeper)?)? == *hash;
+    }
+
+    if !unchanged {
+        return Ok(Outcome::Skipped("the file kept was changed".to_string()));
+    }
+
+    let temporary = temporary_path(&step.source);
+    let copied = copy_file(keeper, &temporary)
+        .and_then(|_| fs::set_permissions(&temporary, metadata.permissions()))
+        .and_then(|_| fs::rename(&temporary, &step.source));
+
+    if let Err(io_error) = copied {
+        let _ = fs::remove_file(&temporary);
+        return Err(FIFError::FileActionError {
+            path: step.source.clone(),
+            io_error,
+        });
+    }
+
+    Ok(Outcome::Done)
+}
+
+/// Copies the content of `from` to a newly created file.
+fn copy_file(from: &Path, to: &Path) -> io::Result<()> {
+    let mut source = File::open(from)?;
+    let mut destination = File::create_new(to)?;
+    io::copy(&mut source, &mut destination)?;
+    destination.sync_all()
+}
+
+/// Removes the temporary files left next to `path` by an interrupted run.
+fn remove_temporary_files(path: &Path) -> FIFResult<()> {
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Ok(());
+    };
+
+    let mut prefix = OsString::from(".");
+    prefix.push(file_name);
+    prefix.push(".fif-");
+    let prefix = prefix.to_string_lossy().into_owned();
+
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Ok(());
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with(&prefix) && name.ends_with(".tmp") {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_journal {
+    use super::*;
+    use crate::{Algorithm, Key};
+    use tempfile::TempDir;
+
+    fn get_entry(operation: Action, source: &Path, target: &Path) -> JournalEntry {
+        JournalEntry {
+            operation,
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+            key: Key {
+                size: 12,
+                hash: Some(Algorithm::Blake3.hash_reader(&b"same content"[..]).unwrap()),
+            },
+            algorithm: Algorithm::Blake3,
+            status: JournalStatus::Started,
+            timestamp: timestamp(),
+        }
+    }
+
+    #[test]
+    fn test_undo_delete() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let keeper = dir.path().join("keeper");
+        let removed = dir.path().join("removed");
+        let skipped = dir.path().join("skipped");
+        fs::write(&keeper, b"same content")?;
+        fs::write(&skipped, b"same content")?;
+
+        let journal_path = dir.path().join("journal.jsonl");
+        let mut journal = Journal::open(&journal_path)?;
+
+        // A completed step.
+        let mut entry = get_entry(Action::Delete, &removed, &keeper);
+        journal.append(&entry)?;
+        entry.status = JournalStatus::Done;
+        journal.append(&entry)?;
+
+        // A skipped step must not be undone.
+        let mut entry = get_entry(Action::Delete, &skipped, &keeper);
+        journal.append(&entry)?;
+        entry.status = JournalStatus::Skipped;
+        journal.append(&entry)?;
+
+        let steps = read_journal(&journal_path)?;
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].status, JournalStatus::Done);
+        assert_eq!(steps[1].status, JournalStatus::Skipped);
+
+        undo_journal(&journal_path)?;
+        assert_eq!(fs::read(&removed)?, b"same content");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_undo_interrupted_symlink() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let keeper = dir.path().join("keeper");
+        let linked = dir.path().join("linked");
+        fs::write(&keeper, b"same content")?;
+        std::os::unix::fs::symlink(&keeper, &linked)?;
+
+        // Interrupted run: a started step, a leftover temporary file and a truncated line.
+        let journal_path = dir.path().join("journal.jsonl");
+        let mut journal = Journal::open(&journal_path)?;
+        journal.append(&get_entry(Action::Symlink, &linked, &keeper))?;
+        fs::write(temporary_path(&linked), b"")?;
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&journal_path)?
+            .write_all(b"{\"Operation\":\"Sym")?;
+
+        undo_journal(&journal_path)?;
+        assert!(!linked.is_symlink());
+        assert_eq!(fs::read(&linked)?, b"same content");
+        assert!(!temporary_path(&linked).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_refuses_changed_keeper() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let keeper = dir.path().join("keeper");
+        let removed = dir.path().join("removed");
+        fs::write(&keeper, b"other conten")?;
+
+        let step = get_entry(Action::Delete, &removed, &keeper);
+        assert!(matches!(copy_from_keeper(&step)?, Outcome::Skipped(_)));
+        assert!(!removed.exists());
+        Ok(())
+    }
+    #[test]
+    fn test_undo_delete_in_quick_mode() -> FIFResult<()> {
+        use crate::{
+            Arguments, GroupExtension, GroupInfo, PathBufExtension, PlanExtension, Procedure,
+        };
+        use clap::Parser;
+
+        let dir = TempDir::new()?;
+        let keeper = dir.path().join("a");
+        let removed = dir.path().join("b");
+        fs::write(&keeper, b"same content")?;
+        fs::write(&removed, b"same content")?;
+
+        let mut arguments =
+            Arguments::parse_from(["find-identical-files", "--quick", "-A", "delete", "-E"]);
+        arguments.journal = dir.path().join("journal.jsonl");
+
+        let groups = [GroupInfo {
+            paths: vec![keeper.clone(), removed.clone()],
+            key: Key::new(12, None)?,
+            num_file: 2,
+            sum_size: 24,
+        }];
+        let groups = groups.get_identical_files(&arguments, Procedure::Sampled)?;
+        let groups = groups.get_verified_files(&arguments, Procedure::Sampled)?;
+
+        // The journal records the entire file hash, not the sampled hash.
+        let entire_file_hash = keeper.get_hash(&arguments, Procedure::EntireFile)?;
+        assert_eq!(groups[0].key.hash, entire_file_hash);
+
+        groups
+            .get_plans(&arguments)
+            .apply_plans(&arguments, Action::Delete)?;
+        assert!(!removed.exists());
+
+        undo_journal(&arguments.journal)?;
+        assert_eq!(fs::read(&removed)?, b"same content");
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+mod delete;
+mod hardlink;
+mod journal;
+mod quarantine;
+#[cfg(target_os = "linux")]
+mod reflink;
+mod symlink;
+
+pub use journal::{Journal, undo_journal};
+pub use quarantine::{absolute_path, quarantine_path, restore_quarantine, write_manifest};
+pub use symlink::symlink_target;
+
+#[cfg(target_os = "linux")]
+use reflink::reflink_duplicate;
+
+use crate::{
+    Action, Arguments, FIFError, FIFResult, JournalEntry, JournalStatus, Key, get_metadata,
+};
+use std::{
+    ffi::OsString,
+    fs::{self, File, Metadata},
+    io::{self, ErrorKind},
+    path::{self, Path, PathBuf},
+    process,
+    time::SystemTime,
+};
+
+/// Result of applying an action to a single duplicate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The action was applied.
+    Done,
+    /// The action was not applied, for the given reason.
+    Skipped(String),
+}
+
+/// Applies the action to a single duplicate, using `keeper` as the file to keep.
+///
+/// The step is appended to the journal before the duplicate is changed,
+/// and again with its final status.
+pub fn apply_action(
+    arguments: &Arguments,
+    action: Action,
+    keeper: &Path,
+    duplicate: &Path,
+    key: &Key,
+    journal: &mut Journal,
+) -> FIFResult<Outcome> {
+    check_unchanged(keeper, key, arguments.start_time)?;
+    check_unchanged(duplicate, key, arguments.start_time)?;
+
+    let target = match (action, &arguments.quarantine_dir) {
+        (Action::Quarantine, Some(quarantine_dir)) => {
+            quarantine::quarantine_path(duplicate, quarantine_dir)?
+        }
+        _ => path::absolute(keeper)?,
+    };
+
+    let mut entry = JournalEntry {
+        operation: action,
+        // Absolute paths, so that the journal can be undone from any directory.
+        source: path::absolute(duplicate)?,
+        target,
+        key: key.clone(),
+        algorithm: arguments.algorithm,
+        status: JournalStatus::Started,
+        timestamp: journal::timestamp(),
+    };
+    journal.append(&entry)?;
+
+    let result = match action {
+        Action::Delete => delete::remove_duplicate(duplicate),
+        Action::HardLink => hardlink::link_duplicate(keeper, duplicate),
+        Action::Reflink => reflink_duplicate(keeper, duplicate, key.size),
+        Action::Symlink => symlink::symlink_duplicate(keeper, duplicate, arguments.symlink_type),
+        Action::Quarantine => match &arguments.quarantine_dir {
+            Some(quarantine_dir) => quarantine::move_duplicate(duplicate, quarantine_dir),
+            None => Ok(Outcome::Skipped("no quarantine directory".to_string())),
+        },
+    };
+
+    entry.status = match &result {
+        Ok(Outcome::Done) => JournalStatus::Done,
+        Ok(Outcome::Skipped(_)) => JournalStatus::Skipped,
+        Err(_) => JournalStatus::Failed,
+    };
+    entry.timestamp = journal::timestamp();
+    journal.append(&entry)?;
+
+    result
+}
+
+/// Reflinks are only available on Linux: the duplicate is skipped.
+#[cfg(not(target_os = "linux"))]
+fn reflink_duplicate(_keeper: &Path, _duplicate: &Path, _size: usize) -> FIFResult<Outcome> {
+    Ok(Outcome::Skipped(
+        "reflinks are only supported on Linux".to_string(),
+    ))
+}
+
+/// Checks that the file still exists with the size found during the search,
+/// and that it was not modified since the search started.
+///
+/// This avoids acting on files that were changed or replaced after being hashed.
+fn check_unchanged(path: &Path, key: &Key, start_time: SystemTime) -> FIFResult<()> {
+    let metadata = get_metadata(&path)?;
+
+    if !metadata.is_file() || metadata.len() != key.size as u64 || metadata.modified()? > start_time
+    {
+        return Err(FIFError::FileChanged {
+            path: path.to_path_buf(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Moves a file without ever overwriting the destination.
+///
+/// A hard link is created at the destination (which fails if the destination
+/// exists) and the source is then removed. If hard links are not possible
+/// (e.g. across file systems), the file is copied to a newly created destination,
+/// keeping its permissions and modification time.
+fn move_no_clobber(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::hard_link(from, to) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => return Err(error),
+        Err(_) => {
+            let metadata = fs::metadata(from)?;
+            let mut source = File::open(from)?;
+            let mut destination = File::create_new(to)?;
+
+            let copied = io::copy(&mut source, &mut destination)
+                .and_then(|_| destination.set_permissions(metadata.permissions()))
+                .and_then(|_| destination.set_modified(metadata.modified()?));
+
+            if let Err(error) = copied {
+                // Do not leave a partial copy behind.
+                let _ = fs::remove_file(to);
+                return Err(error);
+            }
+        }
+    }
+
+    fs::remove_file(from)
+}
+
+/// Builds a hidden temporary path next to `path`, in the same directory
+/// (and therefore on the same file system), suitable for an atomic rename.
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(format!(".fif-{}.tmp", process::id()));
+    path.with_file_name(file_name)
+}
+
+/// Identifier of the device (file system) containing the file.
+#[cfg(unix)]
+pub fn device_id(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+/// Identifier of the device (file system) containing the file.
+#[cfg(not(unix))]
+pub fn device_id(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+/// Identifier of the file: the pair (device, inode).
+#[cfg(unix)]
+pub fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Identifier of the file: the pair (device, inode).
+#[cfg(not(unix))]
+pub fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Number of hard links to the file.
+#[cfg(unix)]
+pub fn link_count(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink()
+}
+
+/// Number of hard links to the file.
+#[cfg(not(unix))]
+pub fn link_count(_metadata: &Metadata) -> u64 {
+    1
+}
+
+#[cfg(test)]
+mod tests_actions {
+    use super::*;
+    use crate::SymlinkType;
+    use clap::Parser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Helper to create a keeper and a duplicate inside a temporary directory.
+    fn create_pair(dir: &TempDir, content: &[u8]) -> FIFResult<(PathBuf, PathBuf)> {
+        let keeper = dir.path().join("keeper");
+        let duplicate = dir.path().join("duplicate");
+        fs::write(&keeper, b"same content")?;
+        fs::write(&duplicate, content)?;
+        Ok((keeper, duplicate))
+    }
+
+    /// Journal stored in its own temporary directory.
+    fn get_journal() -> FIFResult<(TempDir, Journal)> {
+        let dir = TempDir::new()?;
+        let journal = Journal::open(&dir.path().join("journal.jsonl"))?;
+        Ok((dir, journal))
+    }
+
+    /// Default arguments, parsed after the files were created (search start time).
+    fn get_arguments() -> Arguments {
+        Arguments::parse_from(["find-identical-files"])
+    }
+
+    #[test]
+    fn test_delete_duplicate() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let (keeper, duplicate) = create_pair(&dir, b"same content")?;
+        let arguments = get_arguments();
+        let (_journal_dir, mut journal) = get_journal()?;
+
+        let key = Key::new(12, None)?;
+        let outcome = apply_action(
+            &arguments,
+            Action::Delete,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        )?;
+
+        assert_eq!(outcome, Outcome::Done);
+        assert!(keeper.exists());
+        assert!(!duplicate.exists());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_link_duplicate() -> FIFResult<()> {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = TempDir::new()?;
+        let (keeper, duplicate) = create_pair(&dir, b"same content")?;
+        let arguments = get_arguments();
+        let (_journal_dir, mut journal) = get_journal()?;
+
+        let key = Key::new(12, None)?;
+        let outcome = apply_action(
+            &arguments,
+            Action::HardLink,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        )?;
+        assert_eq!(outcome, Outcome::Done);
+
+        let keeper_ino = fs::metadata(&keeper)?.ino();
+        let duplicate_ino = fs::metadata(&duplicate)?.ino();
+        assert_eq!(keeper_ino, duplicate_ino);
+
+        // Only the two paths remain: the temporary link was renamed.
+        assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+
+        // A second run finds the files already linked.
+        let outcome = apply_action(
+            &arguments,
+            Action::HardLink,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        )?;
+        assert!(matches!(outcome, Outcome::Skipped(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reflink_duplicate() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let (keeper, duplicate) = create_pair(&dir, b"same content")?;
+        let arguments = get_arguments();
+        let (_journal_dir, mut journal) = get_journal()?;
+
+        // Done on Btrfs/XFS, skipped on file systems without reflink support (e.g. ext4, tmpfs).
+        let key = Key::new(12, None)?;
+        let outcome = apply_action(
+            &arguments,
+            Action::Reflink,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        )?;
+        match outcome {
+            Outcome::Done => {}
+            Outcome::Skipped(reason) => assert_eq!(reason, reflink::REFLINK_UNSUPPORTED),
+        }
+
+        // In both cases, the files remain separate and unchanged.
+        assert_eq!(fs::read(&duplicate)?, b"same content");
+        assert_eq!(fs::read(&keeper)?, b"same content");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_duplicate() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let keeper = dir.path().join("a/b/keeper");
+        let duplicate = dir.path().join("c/duplicate");
+        fs::create_dir_all(dir.path().join("a/b"))?;
+        fs::create_dir_all(dir.path().join("c"))?;
+        fs::write(&keeper, b"same content")?;
+        fs::write(&duplicate, b"same content")?;
+        let key = Key::new(12, None)?;
+        let (_journal_dir, mut journal) = get_journal()?;
+
+        let mut arguments = get_arguments();
+        arguments.symlink_type = SymlinkType::Relative;
+        apply_action(
+            &arguments,
+            Action::Symlink,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        )?;
+        assert_eq!(fs::read_link(&duplicate)?, PathBuf::from("../a/b/keeper"));
+        assert_eq!(fs::read(&duplicate)?, b"same content");
+
+        let other = dir.path().join("c/other");
+        fs::write(&other, b"same content")?;
+        let mut arguments = get_arguments();
+        arguments.symlink_type = SymlinkType::Absolute;
+        apply_action(
+            &arguments,
+            Action::Symlink,
+            &keeper,
+            &other,
+            &key,
+            &mut journal,
+        )?;
+        assert_eq!(fs::read_link(&other)?, fs::canonicalize(&keeper)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_refuse_changed_size() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let (keeper, duplicate) = create_pair(&dir, b"same content, but appended later")?;
+        let arguments = get_arguments();
+        let (_journal_dir, mut journal) = get_journal()?;
+
+        let key = Key::new(12, None)?;
+        let result = apply_action(
+            &arguments,
+            Action::Delete,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        );
+
+        assert!(matches!(result, Err(FIFError::FileChanged { .. })));
+        assert!(duplicate.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_refuse_changed_modification_time() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let (keeper, duplicate) = create_pair(&dir, b"same content")?;
+        let arguments = get_arguments();
+        let (_journal_dir, mut journal) = get_journal()?;
+
+        // The duplicate is modified after the search started.
+        let file = fs::File::options().write(true).open(&duplicate)?;
+        file.set_modified(arguments.start_time + std::time::Duration::from_secs(60))?;
+
+        let key = Key::new(12, None)?;
+        let result = apply_action(
+            &arguments,
+            Action::Symlink,
+            &keeper,
+            &duplicate,
+            &key,
+            &mut journal,
+        );
+
+        assert!(matches!(result, Err(FIFError::FileChanged { .. })));
+        assert!(!duplicate.is_symlink());
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{
+    FIFError, FIFResult, GroupInfo, MANIFEST_FILENAME, ManifestInfo, PlanInfo, QuarantineFile,
+    open_file, traits::Colors,
+};
+use std::{
+    fs,
+    io::{BufReader, BufWriter, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use super::{Outcome, move_no_clobber};
+
+/// Moves a duplicate into the quarantine directory.
+///
+/// The absolute path of the duplicate is reproduced inside the quarantine directory,
+/// so files with the same name in different directories never collide.
+pub fn move_duplicate(duplicate: &Path, quarantine_dir: &Path) -> FIFResult<Outcome> {
+    let quarantine = quarantine_path(duplicate, quarantine_dir)?;
+
+    if let Some(parent) = quarantine.parent() {
+        fs::create_dir_all(parent).map_err(|io_error| FIFError::FileActionError {
+            path: parent.to_path_buf(),
+            io_error,
+        })?;
+    }
+
+    move_no_clobber(duplicate, &quarantine).map_err(|io_error| FIFError::FileActionError {
+        path: duplicate.to_path_buf(),
+        io_error,
+    })?;
+
+    Ok(Outcome::Done)
+}
+
+/// Returns the path of the file inside the quarantine directory.
+///
+/// Example: `/home/user/a.txt` is moved to `<quarantine_dir>/home/user/a.txt`.
+pub fn quarantine_path(path: &Path, quarantine_dir: &Path) -> FIFResult<PathBuf> {
+    let absolute = absolute_path(path)?;
+
+    let relative: PathBuf = absolute
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+
+    Ok(fs::canonicalize(quarantine_dir)?.join(relative))
+}
+
+/// Canonical path of the parent directory joined with the file name.
+///
+/// Unlike `fs::canonicalize`, a symbolic link is not replaced by its target.
+pub fn absolute_path(path: &Path) -> FIFResult<PathBuf> {
+    let file_name = path.file_name().unwrap_or_default();
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    Ok(fs::canonicalize(parent)?.join(file_name))
+}
+
+/// Writes (or extends) the manifest in the quarantine directory.
+///
+/// The manifest is written before any file is moved, so that an interrupted
+/// run can still be restored: entries whose file was not moved are skipped.
+pub fn write_manifest(plans: &[PlanInfo], quarantine_dir: &Path) -> FIFResult<PathBuf> {
+    let manifest_path = quarantine_dir.join(MANIFEST_FILENAME);
+
+    // Keep the entries of previous runs.
+    let mut manifest: Vec<ManifestInfo> = match manifest_path.exists() {
+        true => read_manifest(&manifest_path)?,
+        false => Vec::new(),
+    };
+
+    for plan_info in plans {
+        let paths: Vec<PathBuf> = std::iter::once(&plan_info.keeper)
+            .chain(&plan_info.duplicates)
+            .cloned()
+            .collect();
+
+        let files = plan_info
+            .duplicates
+            .iter()
+            .map(|duplicate| {
+                Ok(QuarantineFile {
+                    original: absolute_path(duplicate)?,
+                    quarantine: quarantine_path(duplicate, quarantine_dir)?,
+                })
+            })
+            .collect::<FIFResult<Vec<QuarantineFile>>>()?;
+
+        manifest.push(ManifestInfo {
+            group_info: GroupInfo {
+                num_file: paths.len(),
+                sum_size: plan_info.key.size * paths.len(),
+                key: plan_info.key.clone(),
+                paths,
+            },
+            keeper: absolute_path(&plan_info.keeper)?,
+            files,
+        });
+    }
+
+    let file = fs::File::create(&manifest_path)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &manifest)?;
+
+    eprintln!("Write Manifest File: {manifest_path:?}");
+
+    Ok(manifest_path)
+}
+
+/// Reads a quarantine manifest.
+fn read_manifest(manifest_path: &Path) -> FIFResult<Vec<ManifestInfo>> {
+    let file = open_file(&manifest_path)?;
+    let manifest = serde_json::from_reader(BufReader::new(file))?;
+    Ok(manifest)
+}
+
+/// Moves every file listed in the manifest back to its original path.
+///
+/// A file is never overwritten: if the original path exists again,
+/// the file stays in quarantine and the conflict is reported.
+pub fn restore_quarantine(manifest_path: &Path) -> FIFResult<()> {
+    let manifest = read_manifest(manifest_path)?;
+
+    let mut num_restored: usize = 0;
+    let mut num_skipped: usize = 0;
+    let mut num_failed: usize = 0;
+
+    for file in manifest.iter().flat_map(|entry| &entry.files) {
+        match restore_file(file) {
+            Ok(Outcome::Done) => {
+                println!("Restore: {:?}", file.original);
+                num_restored += 1;
+            }
+            Ok(Outcome::Skipped(reason)) => {
+                eprintln!(
+                    "{}: {:?} ({reason})",
+                    "Skipped".yellow().bold(),
+                    file.original
+                );
+                num_skipped += 1;
+            }
+            Err(error) => {
+                eprintln!("{error}");
+                num_failed += 1;
+            }
+        }
+    }
+
+    println!("Number of restored files: {num_restored}");
+    println!("Number of skipped files: {num_skipped}");
+    println!("Number of failed files: {num_failed}\n");
+
+    Ok(())
+}
+
+/// Moves a single file back from quarantine, refusing to overwrite.
+fn restore_file(file: &QuarantineFile) -> FIFResult<Outcome> {
+    if !file.quarantine.exists() {
+        return Ok(Outcome::Skipped("not found in quarantine".to_string()));
+    }
+
+    if let Some(parent) = file.original.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match move_no_clobber(&file.quarantine, &file.original) {
+        Ok(()) => Ok(Outcome::Done),
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => Ok(Outcome::Skipped(
+            "conflict: the original path already exists".to_string(),
+        )),
+        Err(io_error) => Err(FIFError::FileActionError {
+            path: file.original.clone(),
+            io_error,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests_quarantine {
+    use super::*;
+    use crate::Key;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_quarantine_and_restore() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let quarantine_dir = dir.path().join("quarantine");
+        let keeper = dir.path().join("a/keeper");
+        let duplicate = dir.path().join("b/duplicate");
+        fs::create_dir_all(&quarantine_dir)?;
+        fs::create_dir_all(dir.path().join("a"))?;
+        fs::create_dir_all(dir.path().join("b"))?;
+        fs::write(&keeper, b"same content")?;
+        fs::write(&duplicate, b"same content")?;
+
+        let plans = vec![PlanInfo {
+            key: Key::new(12, Some("hash".to_string()))?,
+            keeper: keeper.clone(),
+            duplicates: vec![duplicate.clone()],
+            sum_size: 12,
+        }];
+
+        let manifest_path = write_manifest(&plans, &quarantine_dir)?;
+        move_duplicate(&duplicate, &quarantine_dir)?;
+
+        // The layout of the original path is preserved in quarantine.
+        let quarantine = quarantine_path(&duplicate, &quarantine_dir)?;
+        assert!(quarantine.starts_with(&quarantine_dir));
+        assert!(quarantine.ends_with("b/duplicate"));
+        assert!(quarantine.exists());
+        assert!(!duplicate.exists());
+
+        let manifest = read_manifest(&manifest_path)?;
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].files[0].original, absolute_path(&duplicate)?);
+        assert_eq!(manifest[0].group_info.key.hash.as_deref(), Some("hash"));
+
+        restore_quarantine(&manifest_path)?;
+        assert_eq!(fs::read(&duplicate)?, b"same content");
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_refuses_overwrite() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let original = dir.path().join("file");
+        let quarantine = dir.path().join("quarantine_file");
+        fs::write(&original, b"new content")?;
+        fs::write(&quarantine, b"old content")?;
+
+        let file = QuarantineFile {
+            original: original.clone(),
+            quarantine: quarantine.clone(),
+        };
+
+        let outcome = restore_file(&file)?;
+        assert!(matches!(outcome, Outcome::Skipped(_)));
+        assert_eq!(fs::read(&original)?, b"new content");
+        assert!(quarantine.exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{FIFError, FIFResult, open_file};
+use std::{fs::OpenOptions, io, os::fd::AsRawFd, path::Path};
+
+use super::Outcome;
+
+/// Replaces the content of a duplicate with a reflink (copy-on-write clone) of the keeper.
+///
+/// The data extents are shared, but the files keep separate inodes: permissions,
+/// ownership and timestamps of the duplicate are preserved, and a later change in
+/// one file does not affect the other.
+///
+/// The `FIDEDUPERANGE` ioctl is used first: the kernel locks both files and checks
+/// that the ranges are byte-by-byte identical before sharing the extents.
+/// If the file system does not support deduplication, `FICLONE` is tried instead.
+/// If neither is supported (e.g. ext4), the duplicate is skipped.
+pub fn reflink_duplicate(keeper: &Path, duplicate: &Path, size: usize) -> FIFResult<Outcome> {
+    if size == 0 {
+        return Ok(Outcome::Skipped("empty file".to_string()));
+    }
+
+    let source = open_file(&keeper)?;
+    let destination = OpenOptions::new()
+        .write(true)
+        .open(duplicate)
+        .map_err(|io_error| FIFError::FileActionError {
+            path: duplicate.to_path_buf(),
+            io_error,
+        })?;
+
+    let result = match dedupe_range(source.as_raw_fd(), destination.as_raw_fd(), size as u64) {
+        Err(error) if is_unsupported(&error) => {
+            // SAFETY: both file descriptors are valid for the duration of the call.
+            let code = unsafe {
+                libc::ioctl(
+                    destination.as_raw_fd(),
+                    libc::FICLONE as libc::Ioctl,
+                    source.as_raw_fd(),
+                )
+            };
+            if code == 0 {
+                Ok(Outcome::Done)
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+        other => other,
+    };
+
+    match result {
+        Ok(outcome) => Ok(outcome),
+        Err(error) if error.raw_os_error() == Some(libc::EXDEV) => {
+            Ok(Outcome::Skipped("different file systems".to_string()))
+        }
+        Err(error) if is_unsupported(&error) => {
+            Ok(Outcome::Skipped(REFLINK_UNSUPPORTED.to_string()))
+        }
+        Err(io_error) => Err(FIFError::FileActionError {
+            path: duplicate.to_path_buf(),
+            io_error,
+        }),
+    }
+}
+
+/// Reason given when a duplicate is skipped because reflinks are not supported.
+pub(super) const REFLINK_UNSUPPORTED: &str = "reflinks are not supported by the file system";
+
+/// `_IOWR(0x94, 54, struct file_dedupe_range)`, from `linux/fs.h`.
+const FIDEDUPERANGE: u32 = 0xC018_9436;
+
+/// The kernel confirmed that the ranges are identical.
+const FILE_DEDUPE_RANGE_SAME: i32 = 0;
+
+/// The kernel found that the ranges differ.
+const FILE_DEDUPE_RANGE_DIFFERS: i32 = 1;
+
+/// Maximum length requested per `FIDEDUPERANGE` call (Btrfs limit: 16 MiB).
+const DEDUPE_CHUNK: u64 = 16 * 1024 * 1024;
+
+/// `struct file_dedupe_range` followed by one `struct file_dedupe_range_info`.
+#[repr(C)]
+#[derive(Default)]
+struct FileDedupeRange {
+    src_offset: u64,
+    src_length: u64,
+    dest_count: u16,
+    reserved1: u16,
+    reserved2: u32,
+    info: FileDedupeRangeInfo,
+}
+
+/// `struct file_dedupe_range_info`
+#[repr(C)]
+#[derive(Default)]
+struct FileDedupeRangeInfo {
+    dest_fd: i64,
+    dest_offset: u64,
+    bytes_deduped: u64,
+    status: i32,
+    reserved: u32,
+}
+
+/// Shares the extents of `size` bytes from `source` into `destination`.
+fn dedupe_range(source: i32, destination: i32, size: u64) -> io::Result<Outcome> {
+    let mut offset: u64 = 0;
+
+    while offset < size {
+        let mut range = FileDedupeRange {
+            src_offset: offset,
+            src_length: DEDUPE_CHUNK.min(size - offset),
+            dest_count: 1,
+            info: FileDedupeRangeInfo {
+                dest_fd: i64::from(destination),
+                dest_offset: offset,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // SAFETY: `range` is a valid `file_dedupe_range` with room for one
+        // `file_dedupe_range_info`, as announced by `dest_count`.
+        let code = unsafe {
+            libc::ioctl(
+                source,
+                FIDEDUPERANGE as libc::Ioctl,
+                &mut range as *mut FileDedupeRange,
+            )
+        };
+
+        if code != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        match range.info.status {
+            FILE_DEDUPE_RANGE_SAME => {}
+            FILE_DEDUPE_RANGE_DIFFERS => {
+                return Ok(Outcome::Skipped(
+                    "contents differ (checked by the kernel)".to_string(),
+                ));
+            }
+            status => return Err(io::Error::from_raw_os_error(-status)),
+        }
+
+        if range.info.bytes_deduped == 0 {
+            // No progress: the remaining bytes are not shared, do not report success.
+            return Ok(Outcome::Skipped(format!(
+                "only {offset} of {size} bytes deduplicated"
+            )));
+        }
+
+        offset += range.info.bytes_deduped;
+    }
+
+    Ok(Outcome::Done)
+}
+
+/// Errors returned by file systems that do not support reflinks.
+///
+/// `EINVAL` is not included: it reports an invalid range (e.g. a file changed
+/// since the search), which must not be hidden by a fallback to `FICLONE`.
+fn is_unsupported(error: &io::Error) -> bool {
+    matches!(
+        error.raw_os_error(),
+        Some(libc::EOPNOTSUPP | libc::ENOTTY | libc::ENOSYS)
+    )
+}
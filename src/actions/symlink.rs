@@ -0,0 +1,124 @@
+use crate::{FIFError, FIFResult, SymlinkType};
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use super::{Outcome, temporary_path};
+
+/// Replaces a duplicate with a symbolic link to the keeper.
+///
+/// The link target is either relative to the directory of the duplicate
+/// (preserved by tools such as `rsync`) or the canonical absolute path of the keeper.
+///
+/// As with hard links, the symbolic link is created under a temporary name and
+/// atomically renamed over the duplicate.
+pub fn symlink_duplicate(
+    keeper: &Path,
+    duplicate: &Path,
+    symlink_type: SymlinkType,
+) -> FIFResult<Outcome> {
+    let target = symlink_target(keeper, duplicate, symlink_type)?;
+
+    let temporary = temporary_path(duplicate);
+
+    create_symlink(&target, &temporary).map_err(|io_error| FIFError::FileActionError {
+        path: temporary.clone(),
+        io_error,
+    })?;
+
+    if let Err(io_error) = fs::rename(&temporary, duplicate) {
+        // Do not leave the temporary link behind.
+        let _ = fs::remove_file(&temporary);
+        return Err(FIFError::FileActionError {
+            path: duplicate.to_path_buf(),
+            io_error,
+        });
+    }
+
+    Ok(Outcome::Done)
+}
+
+/// Computes the target of the symbolic link that replaces `duplicate`.
+pub fn symlink_target(
+    keeper: &Path,
+    duplicate: &Path,
+    symlink_type: SymlinkType,
+) -> FIFResult<PathBuf> {
+    let to_action_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |io_error| FIFError::FileActionError { path, io_error }
+    };
+
+    let keeper_path = fs::canonicalize(keeper).map_err(to_action_error(keeper))?;
+
+    let target: PathBuf = match symlink_type {
+        SymlinkType::Absolute => keeper_path,
+        SymlinkType::Relative => {
+            let directory = match duplicate.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let directory = fs::canonicalize(directory).map_err(to_action_error(directory))?;
+            relative_path(&keeper_path, &directory)
+        }
+    };
+
+    Ok(target)
+}
+
+/// Computes the path of `target` relative to the directory `base`.
+///
+/// Both paths must be absolute and canonical (without `.` or `..` components).
+fn relative_path(target: &Path, base: &Path) -> PathBuf {
+    let target_components: Vec<Component> = target.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+
+    let common = target_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+
+    for _ in common..base_components.len() {
+        relative.push("..");
+    }
+
+    for component in &target_components[common..] {
+        relative.push(component);
+    }
+
+    relative
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(test)]
+mod tests_symlink {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        let cases = [
+            ("/a/b/file", "/a/b", "file"),
+            ("/a/b/file", "/a/c", "../b/file"),
+            ("/a/b/file", "/a/c/d", "../../b/file"),
+            ("/x/file", "/a/b", "../../x/file"),
+        ];
+
+        for (target, base, expected) in cases {
+            let relative = relative_path(Path::new(target), Path::new(base));
+            assert_eq!(relative, PathBuf::from(expected));
+        }
+    }
+}
@@ -1,8 +1,17 @@
-use crate::{Algorithm, FIFResult, clear_terminal_screen};
+use crate::{
+    Action, Algorithm, FIFResult, JOURNAL_FILENAME, KeepRule, SymlinkType, Verify,
+    clear_terminal_screen, parse_size, parse_time_limit,
+};
 use clap::{CommandFactory, Parser, ValueEnum};
 use clap_complete::{Generator, Shell, generate};
 use serde::Serialize;
-use std::{fs, io, path::PathBuf, process};
+use std::{
+    fs::{self, Metadata},
+    io,
+    path::{Path, PathBuf},
+    process,
+    time::SystemTime,
+};
 
 #[derive(Debug, Default, Clone, ValueEnum, Serialize)]
 pub enum ResultFormat {
@@ -10,6 +19,8 @@ pub enum ResultFormat {
     Yaml,
     #[default]
     Personal,
+    /// POSIX shell script with one command per duplicate (see --action), to review before running.
+    Shell,
 }
 
 // https://stackoverflow.com/questions/74068168/clap-rs-not-printing-colors-during-help
@@ -40,7 +51,7 @@ const APPLET_TEMPLATE: &str = "\
 {after-help}";
 
 /// Command Line Arguments
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(
     // Read from `Cargo.toml`
     author, version, about,
@@ -54,24 +65,42 @@ pub struct Arguments {
     #[arg(short('a'), long("algorithm"), value_enum, default_value_t = Algorithm::default())]
     pub algorithm: Algorithm,
 
+    /// Choose an action to apply to the identical files.
+    ///
+    /// One file per group is kept (see --keep) and the action is applied to the others.
+    ///
+    /// A dry-run preview is always printed: no file is changed unless --execute is given.
+    #[arg(short('A'), long("action"), value_enum, required = false)]
+    pub action: Option<Action>,
+
     /// Set a minimum file size (in bytes) to search for identical files.
     ///
     /// keep files whose size is greater than or equal to a minimum value.
     ///
+    /// Accepts SI (10K, 2G) and IEC (10KiB, 1.5MiB) unit suffixes.
+    ///
     /// size >= min_size
-    #[arg(short('b'), long("min_size"), required = false, default_value_t = 0)]
+    #[arg(
+        short('b'), long("min_size"),
+        required = false,
+        default_value_t = 0,
+        value_parser = parse_size,
+    )]
     pub min_size: u64,
 
     /// Set a maximum file size (in bytes) to search for identical files.
     ///
     /// keep files whose size is less than or equal to a maximum value.
     ///
+    /// Accepts SI (10K, 2G) and IEC (10KiB, 1.5MiB) unit suffixes.
+    ///
     /// size <= max_size
     #[arg(
         short('B'), long("max_size"),
         required = false,
         default_value_t = u64::MAX,
         hide_default_value = true,
+        value_parser = parse_size,
     )]
     pub max_size: u64,
 
@@ -108,6 +137,35 @@ pub struct Arguments {
     #[arg(short('e'), long("extended_path"), default_value_t = false)]
     pub extended_path: bool,
 
+    /// Execute the chosen action, otherwise only print the dry-run preview.
+    #[arg(
+        short('E'),
+        long("execute"),
+        default_value_t = false,
+        requires = "action"
+    )]
+    pub execute: bool,
+
+    /// Search only the files with these extensions (case insensitive).
+    ///
+    /// May be repeated or separated by commas (e.g. --extension jpg,png).
+    #[arg(long("extension"), required = false, value_delimiter = ',')]
+    pub extension: Vec<String>,
+
+    /// Skip the files with these extensions (case insensitive).
+    ///
+    /// May be repeated or separated by commas (e.g. --exclude_extension tmp,part).
+    #[arg(long("exclude_extension"), required = false, value_delimiter = ',')]
+    pub exclude_extension: Vec<String>,
+
+    /// Exclude the files and directories matching the glob pattern.
+    ///
+    /// May be repeated (e.g. --exclude node_modules --exclude '*.tmp').
+    /// Patterns match the name or the path relative to the input directory.
+    /// Excluded directories are never descended into.
+    #[arg(long("exclude"), required = false)]
+    pub exclude: Vec<String>,
+
     /// Minimum frequency (number of identical files) to be filtered.
     ///
     /// If n = 1, all files will be reported.
@@ -131,6 +189,31 @@ pub struct Arguments {
     )]
     pub max_frequency: u64,
 
+    /// Read the files to compare from this list, instead of walking the input directories.
+    ///
+    /// One path per line, or separated by NUL bytes with --null.
+    /// Use "-" to read the standard input (e.g. fd -e jpg | find-identical-files --files_from -).
+    #[arg(long("files_from"), required = false, conflicts_with = "input_dir")]
+    pub files_from: Option<PathBuf>,
+
+    /// Set the number of initial bytes hashed to filter out the files that differ early.
+    ///
+    /// Formats with identical headers (ISO images, VM disks, videos) need a larger window.
+    /// Accepts unit suffixes (e.g. 64KiB); 0 skips this step.
+    #[arg(long("first_bytes"), default_value = "1KiB", value_parser = parse_size)]
+    pub first_bytes: u64,
+
+    /// Follow symbolic links to files and directories.
+    ///
+    /// Links that lead back to one of their parent directories (loops) are skipped.
+    /// A symbolic link and its target are the same file: they are counted once.
+    #[arg(
+        long("follow_symlinks"),
+        alias("follow-symlinks"),
+        default_value_t = false
+    )]
+    pub follow_symlinks: bool,
+
     /**
     If provided, outputs the completion file for given shell.
 
@@ -209,23 +292,191 @@ pub struct Arguments {
     #[arg(short('g'), long("generate"), value_enum)]
     pub generator: Option<Shell>,
 
-    /// Set the input directory where to search for identical files
+    /// Skip the files ignored by .gitignore, .ignore, .git/info/exclude and the global git excludes.
+    ///
+    /// Ignore files are read in every directory, and in the parents of the input directories:
+    /// rules of deeper directories take precedence, as in ripgrep.
+    #[arg(long("gitignore"), default_value_t = false)]
+    pub gitignore: bool,
+
+    /// Show sizes in human units (e.g. 3.2 GiB) instead of bytes with thousands separators.
+    ///
+    /// Applies to the Personal format of every report (identical files, previews and summaries).
+    #[arg(short('H'), long("human_readable"), default_value_t = false)]
+    pub human_readable: bool,
+
+    /// Set the input directories where to search for identical files
     /// [default: current directory].
-    #[arg(short('i'), long("input_dir"), required = false)]
-    pub input_dir: Option<PathBuf>,
+    ///
+    /// Accepts several directories (e.g. -i /srv/photos /home/*/Pictures).
+    /// Files are grouped across all of them; nested directories are searched only once.
+    #[arg(short('i'), long("input_dir"), required = false, num_args = 1..)]
+    pub input_dir: Vec<PathBuf>,
+
+    /// Skip the files listed in the ignore file (gitignore syntax).
+    ///
+    /// May be repeated. Patterns are relative to the current directory.
+    /// These files have a lower precedence than .gitignore and .ignore files.
+    #[arg(long("ignore_file"), required = false)]
+    pub ignore_file: Vec<PathBuf>,
+
+    /// Search only the files matching the glob pattern, or inside a matching directory.
+    ///
+    /// May be repeated (e.g. --include '*.jpg' --include photos).
+    /// Patterns match the name or the path relative to the input directory.
+    #[arg(long("include"), required = false)]
+    pub include: Vec<String>,
+
+    /// Set the journal file where every step of an action is recorded (JSON Lines).
+    ///
+    /// Each step is appended before the file is changed, so that an interrupted run can be undone (see --undo).
+    #[arg(short('j'), long("journal"), default_value = JOURNAL_FILENAME)]
+    pub journal: PathBuf,
+
+    /// Review the identical files in an interactive terminal interface.
+    ///
+    /// Move between groups, mark the file to keep in each group, preview text files
+    /// and apply the chosen action after a confirmation screen.
+    #[arg(short('I'), long("interactive"), default_value_t = false)]
+    pub interactive: bool,
+
+    /// Choose the rule used to select the file to keep in each group.
+    ///
+    /// Ties are broken by alphabetical order.
+    #[arg(short('k'), long("keep"), value_enum, default_value_t = KeepRule::default())]
+    pub keep: KeepRule,
+
+    /// Set the number of final bytes hashed to filter out the files that differ at the end.
+    ///
+    /// Trailers differ more often than headers for some formats (ISO images, SQLite databases).
+    /// Accepts unit suffixes (e.g. 64KiB); 0 skips this step.
+    #[arg(long("last_bytes"), default_value = "1KiB", value_parser = parse_size)]
+    pub last_bytes: u64,
+
+    /// Compare the files of each group block by block at the same time, instead of hashing each file.
+    ///
+    /// Files that become unique are no longer read: non-duplicates that differ early are read
+    /// much less. The result is the same as with the entire file hash; the hash of one file
+    /// per group is still calculated for the report.
+    #[arg(long("lockstep"), default_value_t = false, conflicts_with = "quick")]
+    pub lockstep: bool,
+
+    /// Search only the files modified after this time.
+    ///
+    /// A duration before now (90s, 45m, 12h, 30d, 2w) or a UTC date (2025-01-31 [08:00:00]).
+    #[arg(long("newer_than"), required = false, value_parser = parse_time_limit)]
+    pub newer_than: Option<SystemTime>,
+
+    /// Paths of --files_from are separated by NUL bytes (find -print0, git ls-files -z).
+    #[arg(
+        short('0'),
+        long("null"),
+        default_value_t = false,
+        requires = "files_from"
+    )]
+    pub null: bool,
+
+    /// Stay on the file system of each input directory: do not descend into mount points.
+    ///
+    /// Directories on another device (NFS mounts, /proc, external drives) are skipped.
+    #[arg(
+        long("one_file_system"),
+        alias("one-file-system"),
+        default_value_t = false
+    )]
+    pub one_file_system: bool,
+
+    /// Search only the files modified before this time.
+    ///
+    /// A duration before now (90s, 45m, 12h, 30d, 2w) or a UTC date (2025-01-31 [08:00:00]).
+    #[arg(long("older_than"), required = false, value_parser = parse_time_limit)]
+    pub older_than: Option<SystemTime>,
 
     /// Omit hidden files (starts with '.'), otherwise search all files.
     #[arg(short('o'), long("omit_hidden"), default_value_t = false)]
     pub omit_hidden: bool,
 
+    /// Add a directory to the priority list used by the keep rules
+    /// first-priority and last-priority.
+    ///
+    /// May be repeated: the order of the directories defines the priority.
+    #[arg(short('p'), long("priority_dir"), required = false)]
+    pub priority_dir: Vec<PathBuf>,
+
+    /// Hash prefixes of growing size (4 KiB, 64 KiB, 1 MiB, 16 MiB, ...) before the entire files.
+    ///
+    /// Groups are split after each round and files that became unique are no longer read:
+    /// large files that differ near the end are separated without reading every byte.
+    #[arg(long("progressive"), default_value_t = false)]
+    pub progressive: bool,
+
+    /// Set the quarantine directory used by the quarantine action.
+    ///
+    /// The duplicates are moved into this directory, reproducing their absolute path,
+    /// and a manifest (fif_manifest.json) is written to restore them later.
+    #[arg(short('q'), long("quarantine_dir"), required = false)]
+    pub quarantine_dir: Option<PathBuf>,
+
+    /// Quick mode: hash N evenly spaced blocks, plus the head and the tail, instead of the entire files.
+    ///
+    /// Probabilistic: files that differ only outside the sampled blocks are reported as identical.
+    /// Files are still compared byte by byte before any action (see --verify).
+    #[arg(
+        long("quick"),
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "16",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub quick: Option<u64>,
+
+    /// Undo the steps recorded in the given journal, in reverse order.
+    ///
+    /// Removed or linked files are recreated from the file kept, and quarantined files are moved back.
+    #[arg(short('u'), long("undo"), required = false)]
+    pub undo: Option<PathBuf>,
+
+    /// Review the identical files one group at a time, with a line-based prompt.
+    ///
+    /// For each group, type the numbers of the files to keep (e.g. 1,3 or 2-4),
+    /// all, skip or quit. The action is applied to the other files (deleted by default).
+    #[arg(
+        short('P'),
+        long("prompt"),
+        default_value_t = false,
+        conflicts_with = "interactive"
+    )]
+    pub prompt: bool,
+
     /// Print the result in the chosen format.
     #[arg(short('r'), long("result_format"), value_enum, default_value_t = ResultFormat::default())]
     pub result_format: ResultFormat,
 
+    /// Restore the files moved to quarantine, reading the given manifest (fif_manifest.json).
+    ///
+    /// Existing files are never overwritten: conflicts are reported and the files stay in quarantine.
+    #[arg(short('R'), long("restore"), required = false)]
+    pub restore: Option<PathBuf>,
+
+    /// Add reference directories, searched along with the input directories.
+    ///
+    /// Files found in a reference directory are always kept: they are never
+    /// deleted, linked or moved. Groups with only reference files are hidden.
+    #[arg(long("reference_dir"), required = false, num_args = 1..)]
+    pub reference_dir: Vec<PathBuf>,
+
     /// Sort result by number of identical files, otherwise sort by file size.
     #[arg(short('s'), long("sort"), default_value_t = false)]
     pub sort: bool,
 
+    /// Choose the kind of symbolic link created by the symlink action.
+    ///
+    /// Relative links are computed from the directory of each duplicate.
+    ///
+    /// Absolute links point to the canonical path of the file kept.
+    #[arg(long("symlink_type"), value_enum, default_value_t = SymlinkType::default())]
+    pub symlink_type: SymlinkType,
+
     /// Show total execution time.
     #[arg(short('t'), long("time"), default_value_t = false)]
     pub time: bool,
@@ -234,6 +485,15 @@ pub struct Arguments {
     #[arg(short('v'), long("verbose"), default_value_t = false)]
     pub verbose: bool,
 
+    /// Compare the identical files byte by byte, after the hash.
+    ///
+    /// Splits any group whose files really differ (hash collision).
+    ///
+    /// By default (auto), the files are verified whenever they may be changed
+    /// (--action, --interactive, --prompt or --result_format shell).
+    #[arg(long("verify"), value_enum, default_value_t = Verify::default())]
+    pub verify: Verify,
+
     /// Wipe (Clear) the terminal screen before listing the identical files.
     ///
     /// On Linux, to clear use the command:
@@ -256,6 +516,12 @@ pub struct Arguments {
     /// XLSX: Excel file
     #[arg(short('x'), long("xlsx_dir"), required = false)]
     pub xlsx_dir: Option<PathBuf>,
+
+    /// Time at which the search started.
+    ///
+    /// Files modified after this time are never changed by an action.
+    #[arg(skip = SystemTime::now())]
+    pub start_time: SystemTime,
 }
 
 impl Arguments {
@@ -275,6 +541,10 @@ impl Arguments {
         args.validate_range_depth()?;
         args.validate_range_frequency()?;
         args.validate_dir_path()?;
+        args.validate_keep_rule()?;
+        args.validate_action()?;
+        args.validate_result_format()?;
+        args.validate_files_from()?;
 
         /*
         // validate simultaneously.
@@ -304,6 +574,14 @@ impl Arguments {
         Ok(args)
     }
 
+    /// Returns the canonical paths of the reference directories.
+    pub fn get_reference_dirs(&self) -> Vec<PathBuf> {
+        self.reference_dir
+            .iter()
+            .map(|dir| fs::canonicalize(dir).unwrap_or_else(|_| dir.clone()))
+            .collect()
+    }
+
     /// Print shell completions to standard output
     fn print_completions<G>(&self, r#gen: G)
     where
@@ -325,6 +603,74 @@ impl Arguments {
         size >= self.min_size && size <= self.max_size
     }
 
+    /// Returns true if the identical files are compared byte by byte (see --verify).
+    ///
+    /// A shell script removes or replaces the duplicates: it is as destructive as an action.
+    pub fn verify_is_enabled(&self) -> bool {
+        let shell_script = matches!(self.result_format, ResultFormat::Shell);
+        self.verify
+            .is_enabled(self.action.is_some() || self.interactive || self.prompt || shell_script)
+    }
+
+    /// Returns true if the identical files were found by sampling, without full comparison.
+    pub fn is_probabilistic(&self) -> bool {
+        self.quick.is_some() && !self.verify_is_enabled()
+    }
+
+    /// Describes how the contents of the identical files were compared.
+    pub fn get_comparison(&self) -> &'static str {
+        if self.verify_is_enabled() || self.lockstep {
+            "Byte by byte"
+        } else if self.quick.is_some() {
+            "Sampled blocks (probabilistic)"
+        } else {
+            "Entire file hash"
+        }
+    }
+
+    /// Returns true if the modification time is within --newer_than and --older_than.
+    pub fn time_is_included(&self, metadata: &Metadata) -> bool {
+        if self.newer_than.is_none() && self.older_than.is_none() {
+            return true;
+        }
+
+        let Ok(modified) = metadata.modified() else {
+            return false;
+        };
+
+        self.newer_than
+            .is_none_or(|newer_than| modified >= newer_than)
+            && self
+                .older_than
+                .is_none_or(|older_than| modified <= older_than)
+    }
+
+    /// Returns true if the extension is allowed by --extension and --exclude_extension.
+    pub fn extension_is_included(&self, path: &Path) -> bool {
+        if self.extension.is_empty() && self.exclude_extension.is_empty() {
+            return true;
+        }
+
+        let extension: String = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let matches = |list: &[String]| {
+            list.iter()
+                .any(|item| item.trim_start_matches('.').to_lowercase() == extension)
+        };
+
+        (self.extension.is_empty() || matches(&self.extension)) && !matches(&self.exclude_extension)
+    }
+
+    /// Returns true if the file passes the size, modification time and extension filters.
+    pub fn file_is_included(&self, path: &Path, metadata: &Metadata) -> bool {
+        self.size_is_included(metadata.len())
+            && self.extension_is_included(path)
+            && self.time_is_included(metadata)
+    }
+
     fn validate_range_size(&self) -> FIFResult<()> {
         if self.min_size > self.max_size {
             eprintln!("fn validate_range_size()");
@@ -361,11 +707,69 @@ impl Arguments {
         Ok(())
     }
 
+    fn validate_keep_rule(&self) -> FIFResult<()> {
+        if self.keep.needs_priority() && self.priority_dir.is_empty() {
+            eprintln!("fn validate_keep_rule()");
+            eprintln!(
+                "The keep rule {} requires at least one --priority_dir",
+                self.keep
+            );
+            process::exit(1);
+        }
+
+        Ok(())
+    }
+
+    fn validate_action(&self) -> FIFResult<()> {
+        if self.action == Some(Action::Quarantine) && self.quarantine_dir.is_none() {
+            eprintln!("fn validate_action()");
+            eprintln!(
+                "The action {} requires --quarantine_dir",
+                Action::Quarantine
+            );
+            process::exit(1);
+        }
+
+        Ok(())
+    }
+
+    fn validate_result_format(&self) -> FIFResult<()> {
+        if matches!(self.result_format, ResultFormat::Shell) && self.execute {
+            eprintln!("fn validate_result_format()");
+            eprintln!("The shell script replaces --execute: review the script, then run it.");
+            process::exit(1);
+        }
+
+        Ok(())
+    }
+
+    fn validate_files_from(&self) -> FIFResult<()> {
+        let from_stdin = self.files_from.as_deref() == Some(Path::new("-"));
+
+        if from_stdin && self.prompt {
+            eprintln!("fn validate_files_from()");
+            eprintln!(
+                "The prompt reads the answers from the standard input: use a file with --files_from."
+            );
+            process::exit(1);
+        }
+
+        Ok(())
+    }
+
     /// Validate directory paths
     fn validate_dir_path(&self) -> FIFResult<()> {
-        let paths = [&self.input_dir, &self.csv_dir, &self.xlsx_dir];
-
-        for dir_path in paths.into_iter().flatten() {
+        // Reference directories are never written to: they may be readonly.
+        let paths = self
+            .input_dir
+            .iter()
+            .chain(&self.csv_dir)
+            .chain(&self.xlsx_dir)
+            .chain(&self.quarantine_dir)
+            .map(|dir_path| (dir_path, true))
+            .chain(self.reference_dir.iter().map(|dir_path| (dir_path, false)));
+
+        for (dir_path, writable) in paths {
             if !std::path::Path::new(&dir_path).try_exists()? {
                 eprintln!("fn validate_dir_path()");
                 eprintln!("The path {dir_path:?} was not found!");
@@ -381,7 +785,7 @@ impl Arguments {
             // Check if able to write inside directory
             let metadada = fs::metadata(dir_path)?;
 
-            if metadada.permissions().readonly() {
+            if writable && metadada.permissions().readonly() {
                 eprintln!("fn validate_dir_path()");
                 eprintln!("No write permission");
                 eprintln!("{dir_path:?} is readonly!");
@@ -0,0 +1,57 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Enum representing the actions that can be applied to identical files.
+///
+/// An action always keeps one file per group (the keeper, see `KeepRule`)
+/// and acts on the remaining duplicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum Action {
+    /// Remove the duplicates, keeping only one file per group.
+    Delete,
+    /// Replace the duplicates with hard links to the file kept.
+    HardLink,
+    /// Share the data of the duplicates with the file kept (copy-on-write clones).
+    ///
+    /// Requires a file system with reflink support, such as Btrfs or XFS.
+    Reflink,
+    /// Replace the duplicates with symbolic links to the file kept (see --symlink_type).
+    Symlink,
+    /// Move the duplicates into the quarantine directory (see --quarantine_dir).
+    ///
+    /// A manifest is written to restore them later (see --restore).
+    Quarantine,
+}
+
+/// Implements `fmt::Display` for `Action` to display variant names in PascalCase.
+impl fmt::Display for Action {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.serialize(formatter).map_err(|_| fmt::Error)
+    }
+}
+
+impl Action {
+    /// Returns the label used to describe what happens to each duplicate.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Delete => "Remove",
+            Action::HardLink => "Hard link",
+            Action::Reflink => "Reflink",
+            Action::Symlink => "Symbolic link",
+            Action::Quarantine => "Quarantine",
+        }
+    }
+}
+
+/// Enum representing the kinds of symbolic link target.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum SymlinkType {
+    /// Target relative to the directory of the link (e.g. ../photos/img.jpg).
+    #[default]
+    Relative,
+    /// Canonical absolute target (e.g. /home/user/photos/img.jpg).
+    Absolute,
+}
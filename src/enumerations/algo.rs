@@ -2,24 +2,29 @@ use ahash::AHasher;
 use blake3::Hasher as Blake3Hasher;
 use clap::ValueEnum;
 use foldhash::fast::FixedState;
+use md5::Md5;
 use rustc_hash::FxHasher;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256, Sha512};
 use std::{
     fmt,
     fs::File,
     hash::{BuildHasher, Hasher},
-    io::{BufReader, Read},
+    io::{BufReader, Cursor, Read, Seek, SeekFrom},
     path::PathBuf,
 };
+use xxhash_rust::xxh3::Xxh3;
 
 use crate::{Arguments, FIFResult, Procedure, open_file};
 
 /// The default buffer size used for reading files in chunks to calculate hashes (64 KB).
 const BUFFER_SIZE: usize = 64 * 1024;
 
-/// The number of initial bytes to hash when a partial (first bytes) hash is requested (1 KB).
-const FIRST_BYTES: usize = 1024;
+/// The size of each block read by the quick mode (4 KB).
+const SAMPLE_BLOCK_SIZE: u64 = 4 * 1024;
+
+/// Prefix of the hashes calculated by the quick mode, to label them as probabilistic.
+const SAMPLED_HASH_PREFIX: &str = "sampled:";
 
 /// Hexadecimal characters for converting bytes to hex strings.
 const HEX_CHARS: [char; 16] = [
@@ -61,7 +66,9 @@ pub trait PathBufExtension {
     ///
     /// Depending on the `Procedure`:
     /// - `Procedure::EntireFile`: Hashes the entire file using the user-selected algorithm.
-    /// - `Procedure::FirstBytes`: Hashes only the first `FIRST_BYTES` for quick filtering.
+    /// - `Procedure::FirstBytes`: Hashes only the first `--first_bytes` for quick filtering.
+    /// - `Procedure::LastBytes`: Hashes only the last `--last_bytes` for quick filtering.
+    /// - `Procedure::Sampled`: Hashes `--quick` evenly spaced blocks, plus the head and the tail.
     /// - `Procedure::Size`: Typically doesn't require a hash, but defaults to first bytes if called.
     fn get_hash(&self, arguments: &Arguments, procedure: Procedure) -> FIFResult<Option<String>>;
 }
@@ -73,49 +80,107 @@ impl PathBufExtension for PathBuf {
         let hash_string: String = match procedure {
             // Full hash is only performed in the final stage
             Procedure::EntireFile => arguments.algorithm.calculate_hash(file)?,
+            // The trailer differs more often than the header for some formats (ISO, VM disks)
+            Procedure::LastBytes => calculate_last_bytes_hash(&mut file, arguments.last_bytes)?,
+            // Quick mode: the sampled blocks replace the entire file
+            Procedure::Sampled => {
+                let samples: u64 = arguments.quick.unwrap_or(1);
+                let hash = calculate_sampled_hash(file, arguments.algorithm, samples)?;
+                format!("{SAMPLED_HASH_PREFIX}{hash}")
+            }
             // All other stages use a fast partial hash of the file header
-            _ => calculate_first_bytes_hash(&mut file)?,
+            _ => calculate_first_bytes_hash(&mut file, arguments.first_bytes)?,
         };
 
         Ok(Some(hash_string))
     }
 }
 
-/// Calculates a hash based on the first `FIRST_BYTES` of the file.
+/// Calculates a hash based on the first `window` bytes of the file.
 ///
-/// This uses `AHasher` for speed on a small, fixed-size chunk of data.
+/// This uses `xxh3` for speed; the window is streamed, so it may be large.
 ///
 /// ### Arguments
 /// * `file` - A mutable reference to the `File` to be hashed.
+/// * `window` - The number of initial bytes to hash (--first_bytes).
 ///
 /// ### Returns
 /// A `FIFResult` containing the hash as a `String` if successful,
 /// or an `Err(MyError)` if an I/O error occurs.
-fn calculate_first_bytes_hash(file: &mut File) -> FIFResult<String> {
-    let mut buffer = [0_u8; FIRST_BYTES];
+fn calculate_first_bytes_hash(file: &mut File, window: u64) -> FIFResult<String> {
+    hash_window(file, window)
+}
+
+/// Calculates a hash based on the last `window` bytes of the file.
+///
+/// ### Arguments
+/// * `file` - A mutable reference to the `File` to be hashed.
+/// * `window` - The number of final bytes to hash (--last_bytes).
+fn calculate_last_bytes_hash(file: &mut File, window: u64) -> FIFResult<String> {
+    let file_size: u64 = file.metadata()?.len();
+    file.seek(SeekFrom::Start(file_size.saturating_sub(window)))?;
+    hash_window(file, window)
+}
+
+/// Calculates a hash of `samples` evenly spaced blocks, plus the first and the last block.
+///
+/// Small files, covered by the blocks, are hashed entirely.
+fn calculate_sampled_hash(mut file: File, algorithm: Algorithm, samples: u64) -> FIFResult<String> {
+    let file_size: u64 = file.metadata()?.len();
+    let num_blocks: u64 = samples.saturating_add(2);
+
+    if file_size <= num_blocks.saturating_mul(SAMPLE_BLOCK_SIZE) {
+        return algorithm.calculate_hash(file);
+    }
 
-    // Read up to FIRST_BYTES bytes. `read` returns the number of bytes read.
-    let count = file.read(&mut buffer)?;
+    // Offsets from the head (block 0) to the tail (block samples + 1).
+    let last_offset: u64 = file_size - SAMPLE_BLOCK_SIZE;
+    let mut buffer: Vec<u8> = Vec::with_capacity((num_blocks * SAMPLE_BLOCK_SIZE) as usize);
+
+    for block in 0..num_blocks {
+        let offset = (last_offset as u128 * block as u128 / (num_blocks - 1) as u128) as u64;
+        file.seek(SeekFrom::Start(offset))?;
+        file.by_ref()
+            .take(SAMPLE_BLOCK_SIZE)
+            .read_to_end(&mut buffer)?;
+    }
 
-    let mut hasher = AHasher::default();
-    hasher.write(&buffer[..count]); // Hash only the bytes that were actually read.
-    Ok(hasher.finish().to_string())
+    algorithm.hash_reader(Cursor::new(buffer))
+}
+
+/// Hashes up to `window` bytes from the current position of the file.
+///
+/// The window is streamed through a fixed buffer: the `xxh3` hash does not
+/// depend on how the reads are split, unlike `AHasher::write`.
+fn hash_window(file: &mut File, window: u64) -> FIFResult<String> {
+    let buffer_size: usize = usize::try_from(window).map_or(BUFFER_SIZE, |w| w.min(BUFFER_SIZE));
+    let mut buffer = vec![0_u8; buffer_size];
+
+    let mut hasher = Xxh3::new();
+    Algorithm::read_and_update(&mut file.take(window), &mut buffer, |chunk| {
+        hasher.update(chunk)
+    })?;
+    Ok(format!("{:016x}", hasher.digest()))
 }
 
 /// Enum representing supported hash algorithms.
 ///
 /// Implements `Display` to allow serializing the enum variant names in PascalCase,
 /// and `ValueEnum` for use with `clap`.
-#[derive(Debug, Default, Clone, Copy, ValueEnum, Serialize)]
+#[derive(Debug, Default, Clone, Copy, ValueEnum, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")] // Serialize enum variants to PascalCase strings
 pub enum Algorithm {
     Ahash,
     #[default]
     Blake3, // Blake3 is generally a good default for speed and security
+    Crc32c, // Checksum used by storage manifests (Castagnoli polynomial)
     Foldhash,
     Fxhash,
+    MD5,    // Used by storage manifests (not collision resistant)
     SHA256, // Cryptographic hash
     SHA512, // Cryptographic hash
+    Xxh3,   // 64 bits, stable across platforms and versions
+    Xxh128, // 128 bits, stable across platforms and versions
 }
 
 /// Implements `fmt::Display` for `Algorithm` to display variant names in PascalCase.
@@ -152,6 +217,13 @@ impl Algorithm {
                 })?;
                 Ok(hasher.finalize().to_string())
             }
+            Algorithm::Crc32c => {
+                let mut crc: u32 = 0;
+                Self::read_and_update(&mut reader, &mut buffer, |chunk| {
+                    crc = crc32c::crc32c_append(crc, chunk);
+                })?;
+                Ok(format!("{crc:08x}"))
+            }
             Algorithm::Foldhash => {
                 let mut hasher = FixedState::default().build_hasher();
                 Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.write(chunk))?;
@@ -162,6 +234,11 @@ impl Algorithm {
                 Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.write(chunk))?;
                 Ok(hasher.finish().to_string())
             }
+            Algorithm::MD5 => {
+                let mut hasher = Md5::new();
+                Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.update(chunk))?;
+                Ok(hasher.finalize().to_hex_string())
+            }
             Algorithm::SHA256 => {
                 let mut hasher = Sha256::new();
                 Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.update(chunk))?;
@@ -172,6 +249,16 @@ impl Algorithm {
                 Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.update(chunk))?;
                 Ok(hasher.finalize().to_hex_string())
             }
+            Algorithm::Xxh3 => {
+                let mut hasher = Xxh3::new();
+                Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.update(chunk))?;
+                Ok(format!("{:016x}", hasher.digest()))
+            }
+            Algorithm::Xxh128 => {
+                let mut hasher = Xxh3::new();
+                Self::read_and_update(&mut reader, &mut buffer, |chunk| hasher.update(chunk))?;
+                Ok(format!("{:032x}", hasher.digest128()))
+            }
         }
     }
 
@@ -200,6 +287,7 @@ mod tests_algo {
         io::{Cursor, Write},
     };
     use tempfile::NamedTempFile; // Temporary file creation for tests
+    use xxhash_rust::xxh3::xxh3_64;
 
     #[test]
     fn test_slice_to_hex_string_empty() {
@@ -318,6 +406,64 @@ mod tests_algo {
         Ok(())
     }
 
+    #[test]
+    fn test_get_md5() -> FIFResult<()> {
+        let empty_hash = hash_bytes_with_algorithm(b"", Algorithm::MD5)?;
+        let hello_hash = hash_bytes_with_algorithm(b"hello world", Algorithm::MD5)?;
+
+        // MD5 has official reference values (RFC 1321)
+        assert_eq!(empty_hash, "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hello_hash, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        // Command: `echo -n "hello world" | md5sum`
+        // Result: 5eb63bbbe01eeed093cb22bb8f5acdc3
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_crc32c() -> FIFResult<()> {
+        let empty_hash = hash_bytes_with_algorithm(b"", Algorithm::Crc32c)?;
+        let check_hash = hash_bytes_with_algorithm(b"123456789", Algorithm::Crc32c)?;
+        let hello_hash = hash_bytes_with_algorithm(b"hello world", Algorithm::Crc32c)?;
+
+        // CRC-32C (Castagnoli) check value, RFC 3720 (iSCSI)
+        assert_eq!(empty_hash, "00000000");
+        assert_eq!(check_hash, "e3069283");
+        assert_eq!(hello_hash, "c99465aa");
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_xxh3() -> FIFResult<()> {
+        let empty_hash = hash_bytes_with_algorithm(b"", Algorithm::Xxh3)?;
+        let hello_hash = hash_bytes_with_algorithm(b"hello world", Algorithm::Xxh3)?;
+
+        println!("xxh3 empty_hash: {empty_hash}");
+        println!("xxh3 hello_hash: {hello_hash}");
+
+        // Reference values of the xxHash repository
+        assert_eq!(empty_hash, "2d06800538d394c2");
+        assert_eq!(hello_hash, "d447b1ea40e6988b");
+        // Command: `echo -n "hello world" | xxhsum -H3`
+        // Result: XXH3_d447b1ea40e6988b  stdin
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_xxh128() -> FIFResult<()> {
+        let empty_hash = hash_bytes_with_algorithm(b"", Algorithm::Xxh128)?;
+        let hello_hash = hash_bytes_with_algorithm(b"hello world", Algorithm::Xxh128)?;
+
+        println!("xxh128 empty_hash: {empty_hash}");
+        println!("xxh128 hello_hash: {hello_hash}");
+
+        // Reference values of the xxHash repository
+        assert_eq!(empty_hash, "99aa06d3014798d86001c324468d497f");
+        assert_eq!(hello_hash, "df8d09e93f874900a99b8775cc15b6c7");
+        // Command: `echo -n "hello world" | xxhsum -H2`
+        // Result: df8d09e93f874900a99b8775cc15b6c7  stdin
+        Ok(())
+    }
+
     #[test]
     fn test_pathbuf_extension_first_bytes_hash() -> FIFResult<()> {
         let content = b"This is a longer test string that should be truncated for the first bytes hash calculation.";
@@ -330,13 +476,105 @@ mod tests_algo {
         // Procedure::FirstBytes should trigger calculate_first_bytes_hash
         let hash = path.get_hash(&args, Procedure::FirstBytes)?;
 
-        // Re-calculate the expected hash manually using only the first FIRST_BYTES.
-        let mut expected_hasher = AHasher::default();
-        let truncated_content = &content[..cmp::min(content.len(), FIRST_BYTES)];
-        expected_hasher.write(truncated_content);
-        let expected_hash = expected_hasher.finish().to_string();
+        // Re-calculate the expected hash manually using only the first bytes.
+        let truncated_content = &content[..cmp::min(content.len(), args.first_bytes as usize)];
+        let expected_hash = format!("{:016x}", xxh3_64(truncated_content));
 
         assert_eq!(hash, Some(expected_hash));
+
+        // A window larger than the read buffer is streamed, with the same hash.
+        let large_content: Vec<u8> = (0..3 * BUFFER_SIZE).map(|i| (i % 251) as u8).collect();
+        let large_file = create_temp_file(&large_content)?;
+        args.first_bytes = (2 * BUFFER_SIZE + 100) as u64;
+        assert_eq!(
+            large_file
+                .path()
+                .to_path_buf()
+                .get_hash(&args, Procedure::FirstBytes)?,
+            Some(format!(
+                "{:016x}",
+                xxh3_64(&large_content[..2 * BUFFER_SIZE + 100])
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pathbuf_extension_last_bytes_hash() -> FIFResult<()> {
+        // Same header, different trailer.
+        let file_a = create_temp_file(b"ISO header ... trailer A")?;
+        let file_b = create_temp_file(b"ISO header ... trailer B")?;
+        let path_a = file_a.path().to_path_buf();
+        let path_b = file_b.path().to_path_buf();
+
+        let mut args = Arguments::build()?;
+        args.first_bytes = 10;
+        args.last_bytes = 9;
+
+        assert_eq!(
+            path_a.get_hash(&args, Procedure::FirstBytes)?,
+            path_b.get_hash(&args, Procedure::FirstBytes)?
+        );
+        assert_ne!(
+            path_a.get_hash(&args, Procedure::LastBytes)?,
+            path_b.get_hash(&args, Procedure::LastBytes)?
+        );
+
+        let expected_hash = format!("{:016x}", xxh3_64(b"trailer A"));
+
+        assert_eq!(
+            path_a.get_hash(&args, Procedure::LastBytes)?,
+            Some(expected_hash)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pathbuf_extension_sampled_hash() -> FIFResult<()> {
+        let mut args = Arguments::build()?;
+        args.quick = Some(2);
+
+        // 4 blocks of 4 KB are sampled: at 0, 1/3, 2/3 and the end of the file.
+        let content = vec![0_u8; 120_000];
+        let mut outside = content.clone();
+        outside[20_000] = 1; // Between the first and the second block
+        let mut inside = content.clone();
+        inside[40_000] = 1; // Inside the second block (offset 38_666)
+
+        let paths: Vec<NamedTempFile> = [&content, &outside, &inside]
+            .into_iter()
+            .map(|content| create_temp_file(content))
+            .collect::<FIFResult<_>>()?;
+        let hashes: Vec<Option<String>> = paths
+            .iter()
+            .map(|file| {
+                file.path()
+                    .to_path_buf()
+                    .get_hash(&args, Procedure::Sampled)
+            })
+            .collect::<FIFResult<_>>()?;
+
+        // Probabilistic: a difference outside the samples is not seen.
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert!(
+            hashes[0]
+                .as_ref()
+                .is_some_and(|hash| hash.starts_with("sampled:"))
+        );
+
+        // Small files are hashed entirely.
+        let small = create_temp_file(b"hello world")?;
+        assert_eq!(
+            small
+                .path()
+                .to_path_buf()
+                .get_hash(&args, Procedure::Sampled)?,
+            Some(format!(
+                "sampled:{}",
+                hash_bytes_with_algorithm(b"hello world", args.algorithm)?
+            ))
+        );
         Ok(())
     }
 
@@ -0,0 +1,191 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{FIFResult, get_metadata};
+
+/// Enum representing the rules used to choose the file to keep in each group.
+///
+/// Ties are always broken by alphabetical order, so the choice is deterministic.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum KeepRule {
+    /// Keep the file with the oldest modification time.
+    #[default]
+    Oldest,
+    /// Keep the file with the newest modification time.
+    Newest,
+    /// Keep the file with the shortest path.
+    ShortestPath,
+    /// Keep the file with the longest path.
+    LongestPath,
+    /// Keep the file found in the first directory of the priority list.
+    FirstPriority,
+    /// Keep the file found in the last directory of the priority list.
+    LastPriority,
+    /// Keep the first file in alphabetical order.
+    Alphabetical,
+}
+
+/// Implements `fmt::Display` for `KeepRule` to display variant names in PascalCase.
+impl fmt::Display for KeepRule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.serialize(formatter).map_err(|_| fmt::Error)
+    }
+}
+
+impl KeepRule {
+    /// Returns true if the rule depends on the directory priority list.
+    pub fn needs_priority(&self) -> bool {
+        matches!(self, KeepRule::FirstPriority | KeepRule::LastPriority)
+    }
+
+    /// Returns the index of the file to keep among `paths`.
+    ///
+    /// Returns `None` only if `paths` is empty.
+    pub fn select_keeper(
+        &self,
+        paths: &[PathBuf],
+        priority_dirs: &[PathBuf],
+    ) -> FIFResult<Option<usize>> {
+        let indices = 0..paths.len();
+
+        let index = match self {
+            KeepRule::Oldest | KeepRule::Newest => {
+                let times = paths
+                    .iter()
+                    .map(|path| Ok(get_metadata(path)?.modified()?))
+                    .collect::<FIFResult<Vec<SystemTime>>>()?;
+
+                if *self == KeepRule::Oldest {
+                    indices.min_by_key(|&i| (times[i], &paths[i]))
+                } else {
+                    indices.min_by_key(|&i| (Reverse(times[i]), &paths[i]))
+                }
+            }
+            KeepRule::ShortestPath => indices.min_by_key(|&i| (path_len(&paths[i]), &paths[i])),
+            KeepRule::LongestPath => {
+                indices.min_by_key(|&i| (Reverse(path_len(&paths[i])), &paths[i]))
+            }
+            KeepRule::FirstPriority | KeepRule::LastPriority => {
+                let dirs: Vec<PathBuf> = priority_dirs.iter().map(|d| canonical(d)).collect();
+                let ranks: Vec<Option<usize>> = paths
+                    .iter()
+                    .map(|path| priority_rank(path, &dirs))
+                    .collect();
+
+                if *self == KeepRule::FirstPriority {
+                    // Files outside the priority list come last.
+                    indices.min_by_key(|&i| (ranks[i].unwrap_or(usize::MAX), &paths[i]))
+                } else {
+                    // Reverse(None) is greater than any Reverse(Some(_)),
+                    // so files outside the priority list also come last.
+                    indices.min_by_key(|&i| (Reverse(ranks[i]), &paths[i]))
+                }
+            }
+            KeepRule::Alphabetical => indices.min_by_key(|&i| &paths[i]),
+        };
+
+        Ok(index)
+    }
+}
+
+/// Number of bytes in the path.
+fn path_len(path: &Path) -> usize {
+    path.as_os_str().len()
+}
+
+/// Canonical form of the path, or the path itself if it cannot be resolved.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Position of the first priority directory that contains the path.
+fn priority_rank(path: &Path, priority_dirs: &[PathBuf]) -> Option<usize> {
+    let path = canonical(path);
+    priority_dirs.iter().position(|dir| path.starts_with(dir))
+}
+
+#[cfg(test)]
+mod tests_keep {
+    use super::*;
+    use std::{
+        fs::File,
+        time::{Duration, UNIX_EPOCH},
+    };
+    use tempfile::TempDir;
+
+    /// Helper to create files (and their parent directories) inside a temporary directory.
+    fn create_files(dir: &TempDir, names: &[&str]) -> FIFResult<Vec<PathBuf>> {
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, b"identical content")?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_by_path_length_and_alphabetical() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths = create_files(&dir, &["bb/file.txt", "a/long/file.txt", "aa/file.txt"])?;
+
+        let shortest = KeepRule::ShortestPath.select_keeper(&paths, &[])?;
+        let longest = KeepRule::LongestPath.select_keeper(&paths, &[])?;
+        let alphabetical = KeepRule::Alphabetical.select_keeper(&paths, &[])?;
+
+        // "aa/file.txt" and "bb/file.txt" have the same length: alphabetical order decides.
+        assert_eq!(shortest, Some(2));
+        assert_eq!(longest, Some(1));
+        assert_eq!(alphabetical, Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_by_modification_time() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths = create_files(&dir, &["a.txt", "b.txt", "c.txt"])?;
+
+        for (path, secs) in paths.iter().zip([2_000, 1_000, 3_000]) {
+            let file = File::options().write(true).open(path)?;
+            file.set_modified(UNIX_EPOCH + Duration::from_secs(secs))?;
+        }
+
+        assert_eq!(KeepRule::Oldest.select_keeper(&paths, &[])?, Some(1));
+        assert_eq!(KeepRule::Newest.select_keeper(&paths, &[])?, Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_by_priority_list() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths = create_files(&dir, &["other/f", "backup/f", "main/f"])?;
+        let priority = [dir.path().join("main"), dir.path().join("backup")];
+
+        assert_eq!(
+            KeepRule::FirstPriority.select_keeper(&paths, &priority)?,
+            Some(2)
+        );
+        assert_eq!(
+            KeepRule::LastPriority.select_keeper(&paths, &priority)?,
+            Some(1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_empty_group() -> FIFResult<()> {
+        assert_eq!(KeepRule::Oldest.select_keeper(&[], &[])?, None);
+        Ok(())
+    }
+}
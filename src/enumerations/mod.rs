@@ -1,2 +1,5 @@
+pub mod action;
 pub mod algo;
+pub mod keep;
 pub mod procedures;
+pub mod verify;
@@ -1,36 +1,60 @@
 use crate::FIFError;
 use std::fmt;
 
+/// Size of the first prefix hashed by the progressive rounds (4 KiB).
+pub const PROGRESSIVE_WINDOW: u64 = 4 * 1024;
+
+/// Growth factor of the prefix between two progressive rounds (4 KiB, 64 KiB, 1 MiB, 16 MiB, ...).
+pub const PROGRESSIVE_GROWTH: u64 = 16;
+
+/// A progressive round hashes at most this fraction of a file (1/4).
+///
+/// Larger prefixes are not worth it: the entire file stage reads the survivors again.
+pub const PROGRESSIVE_MAX_FRACTION: u64 = 4;
+
 /// Represents the stages of the file deduplication pipeline.
 ///
 /// The process follows a "Successive Filtering" strategy:
 /// 1. Compare by Size (Fastest, many false positives).
 /// 2. Compare by Header/First Bytes (Fast, filters out most unique files).
 /// 3. Compare by Full Content Hash (Slower, definitive proof of identity).
+/// 4. Optionally, compare byte by byte (Slowest, rules out hash collisions).
+///
+/// Stages added later keep the numbers above unchanged (see `TryFrom<u8>`):
+/// 5. Compare by Trailer/Last Bytes, after step 2 (Fast, filters out files sharing a header).
+/// 6. In quick mode, compare by Sampled Blocks Hash, instead of step 3 (Fast, probabilistic).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Procedure {
     /// Step 1: Group files sharing the exact same byte count.
     Size = 1,
-    /// Step 2: Group files sharing the same hash of their initial bytes (e.g., first 1KB).
+    /// Step 2: Group files sharing the same hash of their initial bytes (see --first_bytes).
     FirstBytes = 2,
     /// Step 3: Group files sharing the same hash of their entire content.
     EntireFile = 3,
+    /// Step 4: Split groups whose files really differ, comparing them byte by byte.
+    ByteByByte = 4,
+    /// Step 5: Group files sharing the same hash of their final bytes (see --last_bytes).
+    LastBytes = 5,
+    /// Step 6: Group files sharing the same hash of sampled blocks, instead of step 3 (see --quick).
+    Sampled = 6,
 }
 
 impl Procedure {
     /// Validates if the number of files in a group satisfies the filter criteria.
     ///
     /// # Logic:
-    /// - During **Size** and **FirstBytes** stages, we only care about the `min` frequency.
+    /// - During **Size**, **FirstBytes** and **LastBytes** stages, we only care about the `min` frequency.
     ///   We keep any group that *could* potentially be a duplicate.
-    /// - During the **EntireFile** stage (final result), we apply both `min` and `max`
+    /// - During the **EntireFile**, **Sampled** and **ByteByByte** stages (final result), we apply both `min` and `max`
     ///   constraints provided by the user to finalize the report.
     pub fn is_valid_frequency(&self, count: usize, min: usize, max: usize) -> bool {
         match self {
             // Preliminary stages: keep candidates meeting the minimum threshold.
-            Procedure::Size | Procedure::FirstBytes => count >= min,
+            Procedure::Size | Procedure::FirstBytes | Procedure::LastBytes => count >= min,
             // Final stage: apply strict bounds for the final output.
-            Procedure::EntireFile => count >= min && count <= max,
+            Procedure::EntireFile | Procedure::Sampled | Procedure::ByteByByte => {
+                count >= min && count <= max
+            }
         }
     }
 
@@ -39,7 +63,10 @@ impl Procedure {
         match self {
             Procedure::Size => "Number of files of identical size",
             Procedure::FirstBytes => "Number of files with identical first bytes",
+            Procedure::LastBytes => "Number of files with identical last bytes",
             Procedure::EntireFile => "Number of files with identical hashes",
+            Procedure::Sampled => "Number of files with identical samples",
+            Procedure::ByteByByte => "Number of files with identical bytes",
         }
     }
 }
@@ -53,6 +80,9 @@ impl TryFrom<u8> for Procedure {
             1 => Ok(Procedure::Size),
             2 => Ok(Procedure::FirstBytes),
             3 => Ok(Procedure::EntireFile),
+            4 => Ok(Procedure::ByteByByte),
+            5 => Ok(Procedure::LastBytes),
+            6 => Ok(Procedure::Sampled),
             // Returns our custom error variant instead of a simple String.
             _ => Err(FIFError::InvalidProcedure(value)),
         }
@@ -65,3 +95,21 @@ impl fmt::Display for Procedure {
         write!(f, "Step {}", *self as u8)
     }
 }
+
+#[cfg(test)]
+mod tests_procedures {
+    use super::*;
+
+    #[test]
+    fn test_procedure_numbers_are_stable() -> Result<(), FIFError> {
+        // The original steps keep their numbers; new steps are appended.
+        assert_eq!(Procedure::try_from(1)?, Procedure::Size);
+        assert_eq!(Procedure::try_from(2)?, Procedure::FirstBytes);
+        assert_eq!(Procedure::try_from(3)?, Procedure::EntireFile);
+        assert_eq!(Procedure::try_from(4)?, Procedure::ByteByByte);
+        assert_eq!(Procedure::try_from(5)?, Procedure::LastBytes);
+        assert_eq!(Procedure::try_from(6)?, Procedure::Sampled);
+        assert!(Procedure::try_from(7).is_err());
+        Ok(())
+    }
+}
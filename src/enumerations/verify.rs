@@ -0,0 +1,39 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Enum representing when the identical files are verified byte by byte.
+///
+/// Non-cryptographic 64-bit hashes (ahash, foldhash, fxhash) may collide,
+/// so files are compared byte by byte before any file is changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum Verify {
+    /// Verify only when files may be changed (see --action, --interactive, --prompt and shell scripts).
+    #[default]
+    Auto,
+    /// Always verify, also when only reporting.
+    Always,
+    /// Never verify: trust the hash.
+    Never,
+}
+
+/// Implements `fmt::Display` for `Verify` to display variant names in PascalCase.
+impl fmt::Display for Verify {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.serialize(formatter).map_err(|_| fmt::Error)
+    }
+}
+
+impl Verify {
+    /// Returns true if the byte-by-byte stage must be performed.
+    ///
+    /// `destructive` is true when the files may be changed.
+    pub fn is_enabled(&self, destructive: bool) -> bool {
+        match self {
+            Verify::Auto => destructive,
+            Verify::Always => true,
+            Verify::Never => false,
+        }
+    }
+}
@@ -30,6 +30,18 @@ pub enum FIFError {
     #[error("{msg}: '{0}'", msg = "CSV Parsing Error".red().bold())]
     CSVError(#[from] csv::Error),
 
+    /// Error when an action (delete, link, move, ...) fails on a file.
+    #[error("{msg}: '{path:?}'\n{io_error}", msg = "File Action Error".red().bold())]
+    FileActionError {
+        path: PathBuf,
+        #[source]
+        io_error: io::Error,
+    },
+
+    /// Error when a file no longer matches the information collected during the search.
+    #[error("{msg}: '{path:?}'\nThe file was changed after the search!", msg = "File Changed Error".red().bold())]
+    FileChanged { path: PathBuf },
+
     /// Specific error when a file is not found.
     #[error("{msg}: '{path:?}'\nPerhaps some temporary files no longer exist!", msg = "File Not Found Error".red().bold())]
     FileNotFound { path: PathBuf },
@@ -42,10 +54,26 @@ pub enum FIFError {
         io_error: io::Error,
     },
 
+    /// Error when reading the content of a file fails.
+    #[error("{msg}: '{path:?}'\n{io_error}", msg = "File Read Error".red().bold())]
+    FileReadError {
+        path: PathBuf,
+        #[source]
+        io_error: io::Error,
+    },
+
     /// Error when writing to a string buffer or formatting fails.
     #[error("{msg}: '{0}'", msg = "Formatting Error".red().bold())]
     Fmt(#[from] std::fmt::Error),
 
+    /// Error when an ignore file (--ignore_file) cannot be read or parsed.
+    #[error("{msg}: '{0}'", msg = "Ignore File Error".red().bold())]
+    IgnoreError(#[from] ignore::Error),
+
+    /// Error when a glob pattern (--exclude, --include) is invalid.
+    #[error("{msg}: '{0}'", msg = "Invalid Glob Pattern".red().bold())]
+    InvalidPattern(#[from] globset::Error),
+
     /// Error when an invalid integer is provided for a Procedure level.
     #[error("{msg}: '{0}'", msg = "Invalid Procedure Level".red().bold())]
     InvalidProcedure(u8),
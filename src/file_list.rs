@@ -0,0 +1,114 @@
+use crate::{Arguments, FIFError, FIFResult, FileInfo, Key, file_id};
+use rayon::prelude::*;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+/// Collects the files listed in a file, or in the standard input ("-"),
+/// instead of walking the input directories.
+///
+/// Paths are separated by newlines, or by NUL bytes with --null (`find -print0`).
+/// Entries that are not regular files, or that no longer exist, are skipped.
+pub fn get_listed_files(arguments: &Arguments, list_path: &Path) -> FIFResult<Vec<FileInfo>> {
+    let separator: u8 = if arguments.null { b'\0' } else { b'\n' };
+
+    let mut paths: Vec<PathBuf> = if list_path == Path::new("-") {
+        read_file_list(io::stdin().lock(), separator)?
+    } else {
+        let file = File::open(list_path).map_err(|io_error| FIFError::FileOpenError {
+            path: list_path.to_path_buf(),
+            io_error,
+        })?;
+        read_file_list(BufReader::new(file), separator)?
+    };
+
+    // A path listed twice is the same file.
+    paths.sort_unstable();
+    paths.dedup();
+
+    let all_files: Vec<FileInfo> = paths
+        .into_par_iter()
+        .map(|path| -> FIFResult<Option<FileInfo>> {
+            let Ok(metadata) = fs::metadata(&path) else {
+                return Ok(None); // Ignore missing files (e.g., deleted since the listing)
+            };
+            let file_size: u64 = metadata.len();
+
+            if metadata.is_file() && arguments.file_is_included(&path, &metadata) {
+                let key = Key::new(file_size, None)?;
+                let file_id = file_id(&metadata);
+                Ok(Some(FileInfo { key, path, file_id }))
+            } else {
+                Ok(None)
+            }
+        })
+        .collect::<FIFResult<Vec<Option<FileInfo>>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    Ok(all_files)
+}
+
+/// Reads the paths separated by `separator` (empty entries are ignored).
+pub fn read_file_list(mut reader: impl BufRead, separator: u8) -> FIFResult<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut entry: Vec<u8> = Vec::new();
+
+    while reader.read_until(separator, &mut entry)? > 0 {
+        if entry.last() == Some(&separator) {
+            entry.pop();
+        }
+        // Lists written on Windows end lines with "\r\n".
+        if separator == b'\n' && entry.last() == Some(&b'\r') {
+            entry.pop();
+        }
+        if !entry.is_empty() {
+            paths.push(path_from_bytes(&entry));
+        }
+        entry.clear();
+    }
+
+    Ok(paths)
+}
+
+/// Path from raw bytes (any bytes are valid on Unix).
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+/// Path from raw bytes (lossy conversion outside Unix).
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests_file_list {
+    use super::*;
+
+    #[test]
+    fn test_read_file_list() -> FIFResult<()> {
+        let lines = "a.txt\r\n\ndir/b c.txt\nlast";
+        assert_eq!(
+            read_file_list(lines.as_bytes(), b'\n')?,
+            [
+                PathBuf::from("a.txt"),
+                PathBuf::from("dir/b c.txt"),
+                PathBuf::from("last")
+            ]
+        );
+
+        // With NUL separators, newlines are part of the names.
+        let entries = b"one\0two\nlines\0\0";
+        assert_eq!(
+            read_file_list(&entries[..], b'\0')?,
+            [PathBuf::from("one"), PathBuf::from("two\nlines")]
+        );
+        Ok(())
+    }
+}
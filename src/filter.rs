@@ -0,0 +1,342 @@
+use crate::{Arguments, FIFResult};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{
+    Match,
+    gitignore::{Gitignore, GitignoreBuilder},
+};
+use std::{
+    collections::HashMap,
+    env, path,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Ignore files read in each directory, from the deepest to the input directory.
+///
+/// Rules of ignore files in deeper directories take precedence, as in git.
+pub type IgnoreStack = Option<Arc<IgnoreDir>>;
+
+/// Ignore files found in one directory, linked to those of the parent directories.
+#[derive(Debug)]
+pub struct IgnoreDir {
+    parent: IgnoreStack,
+    /// Matchers in order of precedence: .ignore, .gitignore, .git/info/exclude
+    matchers: Vec<Gitignore>,
+}
+
+/// Names of the ignore files read in each directory, in order of precedence.
+const IGNORE_FILES: [&str; 3] = [".ignore", ".gitignore", ".git/info/exclude"];
+
+/// Selects the files and directories found while walking the input directories.
+///
+/// Patterns are matched against the name of the entry and against its path
+/// relative to the input directory (e.g. `target`, `*.tmp` or `photos/2024/*`).
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    /// Entries never searched (directories are not descended into)
+    exclude: GlobSet,
+    /// If any, only the files matching a pattern, or inside a matching directory, are searched
+    include: Option<GlobSet>,
+    /// Read the .gitignore and .ignore files of each directory
+    gitignore: bool,
+    /// Global git excludes and --ignore_file matchers, in order of precedence
+    ignore_files: Vec<Gitignore>,
+}
+
+impl PathFilter {
+    /// Builds the filter from the --exclude, --include, --gitignore and --ignore_file options.
+    pub fn new(arguments: &Arguments) -> FIFResult<Self> {
+        let include = match arguments.include.is_empty() {
+            true => None,
+            false => Some(build_set(&arguments.include)?),
+        };
+
+        let mut ignore_files: Vec<Gitignore> = Vec::new();
+
+        if arguments.gitignore {
+            // Errors in the global excludes are not fatal (as in git).
+            let (global, _error) = Gitignore::global();
+            ignore_files.push(global);
+        }
+
+        // Patterns of custom ignore files are relative to the current directory.
+        for ignore_file in &arguments.ignore_file {
+            let mut builder = GitignoreBuilder::new(env::current_dir()?);
+            if let Some(error) = builder.add(ignore_file) {
+                return Err(error.into());
+            }
+            ignore_files.push(builder.build()?);
+        }
+
+        Ok(PathFilter {
+            exclude: build_set(&arguments.exclude)?,
+            include,
+            gitignore: arguments.gitignore,
+            ignore_files,
+        })
+    }
+
+    /// Returns true if ignore files must be read during the walk.
+    pub fn has_ignore_rules(&self) -> bool {
+        self.gitignore || !self.ignore_files.is_empty()
+    }
+
+    /// Reads the ignore files of the directory and links them to those of its parents.
+    ///
+    /// `dir` must be an absolute path.
+    pub fn read_ignore_files(&self, dir: &Path, parent: IgnoreStack) -> IgnoreStack {
+        if !self.gitignore {
+            return parent;
+        }
+
+        let matchers: Vec<Gitignore> = IGNORE_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                // Invalid lines are skipped, as ripgrep does.
+                let mut builder = GitignoreBuilder::new(dir);
+                builder.add(path);
+                builder.build().ok()
+            })
+            .collect();
+
+        match matchers.is_empty() {
+            true => parent,
+            false => Some(Arc::new(IgnoreDir { parent, matchers })),
+        }
+    }
+
+    /// Returns true if the path is ignored by the ignore files.
+    ///
+    /// `path` must be an absolute path; `stack` holds the ignore files of its directory.
+    pub fn is_ignored(&self, stack: Option<&IgnoreDir>, path: &Path, is_dir: bool) -> bool {
+        let mut ignore_dir = stack;
+
+        while let Some(dir) = ignore_dir {
+            for matcher in &dir.matchers {
+                match matcher.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            ignore_dir = dir.parent.as_deref();
+        }
+
+        self.ignore_files
+            .iter()
+            .map(|matcher| matcher.matched(path, is_dir))
+            .find(|matched| !matched.is_none())
+            .is_some_and(|matched| matched.is_ignore())
+    }
+
+    /// Returns true if the directory must not be descended into.
+    ///
+    /// `relative` is the path of the directory relative to the input directory.
+    pub fn is_excluded_dir(&self, relative: &Path) -> bool {
+        !relative.as_os_str().is_empty() && is_match(&self.exclude, relative)
+    }
+
+    /// Returns true if the file must be searched.
+    ///
+    /// `relative` is the path of the file relative to the input directory.
+    pub fn is_included_file(&self, relative: &Path) -> bool {
+        if is_match(&self.exclude, relative) {
+            return false;
+        }
+
+        match &self.include {
+            Some(include) => relative
+                .ancestors()
+                .filter(|path| !path.as_os_str().is_empty())
+                .any(|path| is_match(include, path)),
+            None => true,
+        }
+    }
+}
+
+/// Applies the [`PathFilter`] to the entries of one input directory.
+#[derive(Debug, Clone)]
+pub struct RootFilter {
+    filter: Arc<PathFilter>,
+    /// Input directory, as given to the walk
+    root: PathBuf,
+    /// Absolute path of the input directory, to match the ignore files
+    absolute_root: PathBuf,
+}
+
+impl RootFilter {
+    /// Creates the filter of the input directory `root`.
+    pub fn new(filter: &Arc<PathFilter>, root: &Path) -> FIFResult<Self> {
+        Ok(RootFilter {
+            filter: Arc::clone(filter),
+            root: root.to_path_buf(),
+            absolute_root: path::absolute(root)?,
+        })
+    }
+
+    /// Returns true if ignore files must be read during the walk.
+    pub fn has_ignore_rules(&self) -> bool {
+        self.filter.has_ignore_rules()
+    }
+
+    /// Path relative to the input directory.
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    /// Ignore files of the parent directories of the input directory.
+    pub fn root_stack(&self) -> IgnoreStack {
+        if !self.filter.gitignore {
+            return None;
+        }
+
+        let mut ancestors: Vec<&Path> = self.absolute_root.ancestors().skip(1).collect();
+        ancestors.reverse();
+
+        ancestors
+            .into_iter()
+            .fold(None, |stack, dir| self.filter.read_ignore_files(dir, stack))
+    }
+
+    /// Reads the ignore files of a directory found during the walk.
+    pub fn read_ignore_files(&self, dir: &Path, parent: IgnoreStack) -> IgnoreStack {
+        let absolute = self.absolute_root.join(self.relative(dir));
+        self.filter.read_ignore_files(&absolute, parent)
+    }
+
+    /// Ignore files of a directory, reading those of its parents first if needed.
+    ///
+    /// The `cache` is used by walks that do not keep a state per directory.
+    pub fn dir_stack(&self, dir: &Path, cache: &mut HashMap<PathBuf, IgnoreStack>) -> IgnoreStack {
+        if let Some(stack) = cache.get(dir) {
+            return stack.clone();
+        }
+
+        // The parent of the input directory holds the ignore files of its ancestors.
+        let relative = self.relative(dir);
+        let parent: IgnoreStack = match dir.parent() {
+            Some(parent) if !relative.as_os_str().is_empty() && relative != dir => {
+                self.dir_stack(parent, cache)
+            }
+            _ => self.root_stack(),
+        };
+
+        let stack = self.read_ignore_files(dir, parent);
+        cache.insert(dir.to_path_buf(), stack.clone());
+        stack
+    }
+
+    /// Returns true if the directory must be descended into.
+    ///
+    /// `stack` holds the ignore files of the parent directory.
+    pub fn keep_dir(&self, path: &Path, stack: Option<&IgnoreDir>) -> bool {
+        let relative = self.relative(path);
+
+        // The input directory itself is always searched.
+        if relative.as_os_str().is_empty() {
+            return true;
+        }
+
+        !self.filter.is_excluded_dir(relative) && !self.is_ignored(relative, stack, true)
+    }
+
+    /// Returns true if the file must be searched.
+    ///
+    /// `stack` holds the ignore files of the parent directory.
+    pub fn keep_file(&self, path: &Path, stack: Option<&IgnoreDir>) -> bool {
+        let relative = self.relative(path);
+        self.filter.is_included_file(relative) && !self.is_ignored(relative, stack, false)
+    }
+
+    /// Returns true if the entry is ignored by the ignore files.
+    fn is_ignored(&self, relative: &Path, stack: Option<&IgnoreDir>, is_dir: bool) -> bool {
+        self.filter.has_ignore_rules()
+            && self
+                .filter
+                .is_ignored(stack, &self.absolute_root.join(relative), is_dir)
+    }
+}
+
+/// Compiles the glob patterns into a single set.
+fn build_set(patterns: &[String]) -> FIFResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Matches the name of the entry or its relative path.
+fn is_match(set: &GlobSet, relative: &Path) -> bool {
+    relative
+        .file_name()
+        .is_some_and(|file_name| set.is_match(file_name))
+        || set.is_match(relative)
+}
+
+#[cfg(test)]
+mod tests_filter {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_exclude_and_include() -> FIFResult<()> {
+        let arguments = Arguments::parse_from([
+            "find-identical-files",
+            "--exclude",
+            "node_modules",
+            "--exclude",
+            "*.tmp",
+            "--include",
+            "photos",
+            "--include",
+            "*.jpg",
+        ]);
+        let filter = PathFilter::new(&arguments)?;
+
+        assert!(filter.is_excluded_dir(Path::new("app/node_modules")));
+        assert!(!filter.is_excluded_dir(Path::new("app/src")));
+        // The input directory itself is never excluded.
+        assert!(!filter.is_excluded_dir(Path::new("")));
+
+        assert!(filter.is_included_file(Path::new("a/b.jpg")));
+        assert!(filter.is_included_file(Path::new("photos/2024/raw.cr2")));
+        assert!(!filter.is_included_file(Path::new("photos/b.tmp")));
+        assert!(!filter.is_included_file(Path::new("docs/a.pdf")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hierarchical_ignore_files() -> FIFResult<()> {
+        use std::fs;
+
+        let dir = tempfile::TempDir::new()?;
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/build"))?;
+        fs::write(root.join(".gitignore"), "build/\n*.log\n")?;
+        // A deeper ignore file takes precedence.
+        fs::write(root.join("app/.gitignore"), "!keep.log\n")?;
+
+        let arguments = Arguments::parse_from(["find-identical-files", "--gitignore"]);
+        let filter = Arc::new(PathFilter::new(&arguments)?);
+        let root_filter = RootFilter::new(&filter, root)?;
+
+        let mut cache = HashMap::new();
+        let app = root_filter.dir_stack(&root.join("app"), &mut cache);
+        let app = app.as_deref();
+
+        assert!(!root_filter.keep_dir(&root.join("app/build"), app));
+        assert!(!root_filter.keep_file(&root.join("app/debug.log"), app));
+        assert!(root_filter.keep_file(&root.join("app/keep.log"), app));
+        assert!(root_filter.keep_file(&root.join("app/main.rs"), app));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let arguments = Arguments::parse_from(["find-identical-files", "--exclude", "a[b"]);
+        assert!(PathFilter::new(&arguments).is_err());
+    }
+}
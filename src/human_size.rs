@@ -0,0 +1,122 @@
+use crate::{Arguments, get_thousands_separator, split_and_insert};
+
+/// Units of the sizes shown by --human_readable (powers of 1024).
+const IEC_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Parses a size in bytes, with an optional SI or IEC unit suffix (case insensitive).
+///
+/// SI units are powers of 1000: `10K`, `10KB`, `2G` or `2GB`.
+///
+/// IEC units are powers of 1024: `10Ki`, `10KiB`, `1.5MiB` or `2GiB`.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let invalid = || format!("expected a size such as 4096, 10K, 1.5MiB or 2G: {text}");
+
+    let split = text
+        .find(|character: char| !character.is_ascii_digit() && character != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "p" | "pb" => 1_000_000_000_000_000,
+        "ki" | "kib" => 1 << 10,
+        "mi" | "mib" => 1 << 20,
+        "gi" | "gib" => 1 << 30,
+        "ti" | "tib" => 1 << 40,
+        "pi" | "pib" => 1 << 50,
+        _ => return Err(invalid()),
+    };
+
+    // Integers are computed exactly (e.g., u64::MAX).
+    if let Ok(integer) = number.parse::<u64>() {
+        return integer
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("size too large: {text}"));
+    }
+
+    match number.parse::<f64>() {
+        Ok(decimal) if decimal.is_finite() => {
+            let size = (decimal * multiplier as f64).round();
+            if size < u64::MAX as f64 {
+                Ok(size as u64)
+            } else {
+                Err(format!("size too large: {text}"))
+            }
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Formats a size with an IEC unit and one decimal place (e.g., 3.2 GiB).
+pub fn format_size(size: usize) -> String {
+    let mut value = size as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < IEC_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{size} B"),
+        _ => format!("{value:.1} {}", IEC_UNITS[unit]),
+    }
+}
+
+/// Formats a size for the reports: in human units with --human_readable,
+/// otherwise in bytes with thousands separators (e.g., 1,234 bytes).
+pub fn display_size(size: usize, arguments: &Arguments) -> String {
+    if arguments.human_readable {
+        format_size(size)
+    } else {
+        let digits =
+            split_and_insert(size, get_thousands_separator()).unwrap_or_else(|_| size.to_string());
+        format!("{digits} bytes")
+    }
+}
+
+#[cfg(test)]
+mod tests_human_size {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("10K"), Ok(10_000));
+        assert_eq!(parse_size("10kb"), Ok(10_000));
+        assert_eq!(parse_size("10KiB"), Ok(10_240));
+        assert_eq!(parse_size("1.5MiB"), Ok(1_572_864));
+        assert_eq!(parse_size("1.5 MB"), Ok(1_500_000));
+        assert_eq!(parse_size("2G"), Ok(2_000_000_000));
+        assert_eq!(parse_size("2gi"), Ok(2_147_483_648));
+        assert_eq!(parse_size("18446744073709551615"), Ok(u64::MAX));
+        assert!(parse_size("20000P").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("MiB").is_err());
+        assert!(parse_size("-1").is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(1_572_864), "1.5 MiB");
+        assert_eq!(format_size(3_435_973_837), "3.2 GiB");
+    }
+
+    #[test]
+    fn test_display_size() {
+        let mut arguments = Arguments::parse_from(["find-identical-files"]);
+        assert!(display_size(1_572_864, &arguments).ends_with(" bytes"));
+
+        arguments.human_readable = true;
+        assert_eq!(display_size(1_572_864, &arguments), "1.5 MiB");
+    }
+}
@@ -1,10 +1,19 @@
+mod actions;
 mod args;
 mod enumerations;
 mod error;
 mod excel;
+mod file_list;
+mod filter;
+mod human_size;
+mod lockstep;
+mod prompt;
 mod separator;
+mod shell;
 mod structures;
+mod time_limit;
 mod traits;
+mod tui;
 
 // https://crates.io/crates/cfg-if
 cfg_if::cfg_if! {
@@ -19,30 +28,51 @@ cfg_if::cfg_if! {
 }
 
 pub use self::{
-    args::Arguments,
+    actions::{
+        Journal, Outcome, absolute_path, apply_action, device_id, file_id, link_count,
+        quarantine_path, restore_quarantine, symlink_target, undo_journal, write_manifest,
+    },
+    args::{Arguments, ResultFormat},
+    enumerations::action::{Action, SymlinkType},
     enumerations::algo::{Algorithm, PathBufExtension, SliceExtension},
+    enumerations::keep::KeepRule,
     enumerations::procedures::*,
+    enumerations::verify::Verify,
     error::*,
     separator::get_thousands_separator,
-    structures::file_info::{FileExtension, FileInfo},
+    structures::action_info::ActionInfo,
+    structures::file_info::{FileExtension, FileInfo, collapse_hard_links},
     structures::group_info::{GroupExtension, GroupInfo},
+    structures::journal_info::{JournalEntry, JournalStatus},
     structures::key_info::Key,
+    structures::manifest_info::{ManifestInfo, QuarantineFile},
     structures::path_info::PathInfo,
+    structures::plan_info::{PlanExtension, PlanInfo},
     structures::total_info::TotalInfo,
 };
 pub use excel::write_xlsx;
-use serde::Serializer;
+pub use file_list::{get_listed_files, read_file_list};
+pub use filter::{IgnoreDir, IgnoreStack, PathFilter, RootFilter};
+pub use human_size::{display_size, format_size, parse_size};
+pub use lockstep::partition_by_content;
+pub use prompt::{Selection, parse_selection, prompt_groups};
+use serde::{Deserialize, Deserializer, Serializer};
+pub use shell::{SHELL_HEADER, shell_quote, write_commands, write_comment};
 use std::{
     fmt::{self, Write as FmtWrite}, // Rename to avoid conflict
-    fs::{self, File},
+    fs::{self, File, Metadata},
     io::{self, Write as IoWrite}, // Rename to avoid conflict
     path::{Path, PathBuf},
     process::Command,
     str,
 };
+pub use time_limit::parse_time_limit;
+pub use tui::review_groups;
 
 pub const CSV_FILENAME: &str = "fif.csv";
 pub const XLSX_FILENAME: &str = "fif.xlsx";
+pub const MANIFEST_FILENAME: &str = "fif_manifest.json";
+pub const JOURNAL_FILENAME: &str = "fif_journal.jsonl";
 
 /// Opens a file in read-only mode.
 ///
@@ -69,18 +99,68 @@ where
         })
 }
 
-/// Get path from arguments or from default (current directory).
-pub fn get_path(arguments: &Arguments) -> FIFResult<PathBuf> {
-    let path: PathBuf = match &arguments.input_dir {
-        Some(path) => path.to_owned(),
-        None => PathBuf::from("."),
+/// Queries the file system to get information about a file.
+///
+/// Provides the same informative error messages as [`open_file`].
+pub fn get_metadata<P>(path: &P) -> FIFResult<Metadata>
+where
+    P: AsRef<Path> + std::fmt::Debug,
+{
+    fs::metadata(path.as_ref()).map_err(|error| {
+        let path_buf = path.as_ref().to_path_buf();
+        match error.kind() {
+            io::ErrorKind::NotFound => FIFError::FileNotFound { path: path_buf },
+            io::ErrorKind::PermissionDenied => FIFError::PermissionDenied { path: path_buf },
+            _ => FIFError::FileOpenError {
+                path: path_buf,
+                io_error: error,
+            },
+        }
+    })
+}
+
+/// Get paths from arguments or from default (current directory),
+/// followed by the reference directories.
+///
+/// Overlapping roots are removed: a root equal to, or inside, another root
+/// is dropped, so that each file is found only once.
+pub fn get_paths(arguments: &Arguments) -> FIFResult<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = match arguments.input_dir.is_empty() {
+        true => vec![PathBuf::from(".")],
+        false => arguments.input_dir.clone(),
     };
+    paths.extend(arguments.reference_dir.iter().cloned());
 
-    if arguments.extended_path {
-        Ok(fs::canonicalize(path)?) // full path
-    } else {
-        Ok(path) // relative path
+    // Compare the canonical paths (symbolic links and `..` resolved).
+    let canonical_paths: Vec<PathBuf> = paths
+        .iter()
+        .map(fs::canonicalize)
+        .collect::<io::Result<_>>()?;
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+
+    for (index, (path, canonical)) in paths.iter().zip(&canonical_paths).enumerate() {
+        let overlaps = canonical_paths
+            .iter()
+            .enumerate()
+            .any(|(other, other_canonical)| {
+                // Inside another root, or equal to a previous root.
+                (canonical != other_canonical && canonical.starts_with(other_canonical))
+                    || (canonical == other_canonical && other < index)
+            });
+
+        if overlaps {
+            continue;
+        }
+
+        if arguments.extended_path {
+            roots.push(canonical.clone()); // full path
+        } else {
+            roots.push(path.clone()); // relative path
+        }
     }
+
+    Ok(roots)
 }
 
 /// Prints the provided byte buffer to standard output as a UTF-8 string.
@@ -195,6 +275,33 @@ where
     serializer.collect_str(&BytesFormatter(*size))
 }
 
+/// Serde Deserializer: the inverse of [`add_thousands_separator`].
+///
+/// Accepts an integer or a string with separators (e.g., "1.234 bytes"),
+/// so that serialized reports can be read back.
+pub fn remove_thousands_separator<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Integer(usize),
+        Text(String),
+    }
+
+    match Size::deserialize(deserializer)? {
+        Size::Integer(size) => Ok(size),
+        Size::Text(text) => text
+            .trim_end_matches("bytes")
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .map_err(serde::de::Error::custom),
+    }
+}
+
 #[cfg(test)]
 mod tests_lib {
     use super::*;
@@ -229,4 +336,49 @@ mod tests_lib {
         assert_eq!(valid, result);
         Ok(())
     }
+
+    #[test]
+    /// cargo test -- --show-output get_paths_without_overlap
+    fn get_paths_without_overlap() -> FIFResult<()> {
+        use clap::Parser;
+
+        let dir = tempfile::TempDir::new()?;
+        let photos = dir.path().join("photos");
+        let backup = dir.path().join("backup");
+        fs::create_dir_all(photos.join("2024"))?;
+        fs::create_dir_all(&backup)?;
+
+        // A nested root and a repeated root are walked only once.
+        let arguments = Arguments::parse_from([
+            "find-identical-files".as_ref(),
+            "-i".as_ref(),
+            photos.join("2024").as_os_str(),
+            photos.as_os_str(),
+            backup.as_os_str(),
+            backup.join("..").join("backup").as_os_str(),
+        ]);
+
+        assert_eq!(get_paths(&arguments)?, vec![photos, backup]);
+        Ok(())
+    }
+
+    #[test]
+    /// cargo test -- --show-output deserialize_group_info
+    fn deserialize_group_info() -> FIFResult<()> {
+        let group_info = GroupInfo {
+            paths: vec![PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")],
+            key: Key::new(1_234_567, Some("abc".to_string()))?,
+            num_file: 2,
+            sum_size: 2_469_134,
+        };
+
+        let serialized = serde_json::to_string_pretty(&group_info)?;
+        println!("{serialized}");
+
+        let deserialized: GroupInfo = serde_json::from_str(&serialized)?;
+        assert_eq!(deserialized.key, group_info.key);
+        assert_eq!(deserialized.sum_size, group_info.sum_size);
+        assert_eq!(deserialized.paths, group_info.paths);
+        Ok(())
+    }
 }
@@ -0,0 +1,200 @@
+use crate::{FIFError, FIFResult, open_file};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+/// Size of the blocks read from each file at the same time (64 KB).
+const BLOCK_SIZE: usize = 64 * 1024;
+
+/// Maximum number of files kept open by all the groups compared at the same time.
+///
+/// Groups are compared in parallel, so each thread gets an equal share.
+/// Larger groups reopen each file for every block, to stay below the
+/// limit of open file descriptors.
+const MAX_OPEN_FILES: usize = 256;
+
+/// Maximum number of files kept open while comparing a group in the current thread pool.
+fn max_open_files_per_group() -> usize {
+    MAX_OPEN_FILES / rayon::current_num_threads().max(1)
+}
+
+/// A file read block by block, opened on demand.
+struct BlockReader {
+    path: PathBuf,
+    file: Option<File>,
+    offset: u64,
+    keep_open: bool,
+}
+
+impl BlockReader {
+    fn new(path: PathBuf, keep_open: bool) -> Self {
+        BlockReader {
+            path,
+            file: None,
+            offset: 0,
+            keep_open,
+        }
+    }
+
+    /// Reads the next block (shorter only at the end of the file).
+    fn read_block(&mut self, buffer: &mut Vec<u8>) -> FIFResult<()> {
+        let mut file = match self.file.take() {
+            Some(file) => file,
+            None => {
+                let mut file = open_file(&self.path)?;
+                file.seek(SeekFrom::Start(self.offset))?;
+                file
+            }
+        };
+
+        buffer.clear();
+        (&mut file)
+            .take(BLOCK_SIZE as u64)
+            .read_to_end(buffer)
+            .map_err(|io_error| FIFError::FileReadError {
+                path: self.path.clone(),
+                io_error,
+            })?;
+
+        self.offset += buffer.len() as u64;
+        if self.keep_open {
+            self.file = Some(file);
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits the files into groups of identical content, comparing them byte by byte.
+///
+/// All files are read block by block at the same time (in lockstep).
+/// After each block, a group is split by block content, and files that
+/// became unique are no longer read. Only groups of two or more files are returned.
+pub fn partition_by_content(paths: &[PathBuf]) -> FIFResult<Vec<Vec<PathBuf>>> {
+    let keep_open = paths.len() <= max_open_files_per_group();
+    let mut readers: Vec<BlockReader> = paths
+        .iter()
+        .map(|path| BlockReader::new(path.clone(), keep_open))
+        .collect();
+    let mut buffers: Vec<Vec<u8>> = vec![Vec::with_capacity(BLOCK_SIZE); paths.len()];
+
+    let mut pending: Vec<Vec<usize>> = vec![(0..paths.len()).collect()];
+    let mut identical: Vec<Vec<usize>> = Vec::new();
+
+    while let Some(group) = pending.pop() {
+        for &index in &group {
+            readers[index].read_block(&mut buffers[index])?;
+        }
+
+        // Split the group: files with the same block stay together.
+        let mut subgroups: Vec<Vec<usize>> = Vec::new();
+        for index in group {
+            match subgroups
+                .iter_mut()
+                .find(|subgroup| buffers[subgroup[0]] == buffers[index])
+            {
+                Some(subgroup) => subgroup.push(index),
+                None => subgroups.push(vec![index]),
+            }
+        }
+
+        for subgroup in subgroups {
+            if subgroup.len() < 2 {
+                // Unique file: stop reading it.
+                readers[subgroup[0]].file = None;
+            } else if buffers[subgroup[0]].is_empty() {
+                // End of file reached by all members.
+                subgroup
+                    .iter()
+                    .for_each(|&index| readers[index].file = None);
+                identical.push(subgroup);
+            } else {
+                pending.push(subgroup);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = identical
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|index| paths[index].clone())
+                .collect()
+        })
+        .collect();
+
+    // Keep the order of the original paths.
+    groups.sort();
+
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests_lockstep {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_partition_by_content() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+
+        // Same size, but the content differs after the first block.
+        let mut content_a = vec![7_u8; BLOCK_SIZE + 10];
+        let mut content_b = content_a.clone();
+        content_a[BLOCK_SIZE + 5] = 1;
+        content_b[BLOCK_SIZE + 5] = 2;
+
+        let contents = [&content_a, &content_b, &content_a, &content_b, &content_a];
+        let paths: Vec<PathBuf> = contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let path = dir.path().join(format!("file_{i}"));
+                fs::write(&path, content).map(|_| path)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let groups = partition_by_content(&paths)?;
+
+        assert_eq!(
+            groups,
+            vec![
+                vec![paths[0].clone(), paths[2].clone(), paths[4].clone()],
+                vec![paths[1].clone(), paths[3].clone()],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_open_files_per_group() -> Result<(), rayon::ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(8).build()?;
+
+        // The budget is shared by the groups compared in parallel.
+        assert_eq!(pool.install(max_open_files_per_group), MAX_OPEN_FILES / 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partition_unique_and_empty_files() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths: Vec<PathBuf> = ["", "", "x"]
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let path = dir.path().join(format!("file_{i}"));
+                fs::write(&path, content).map(|_| path)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let groups = partition_by_content(&paths)?;
+
+        // Two empty files are identical; the unique file is dropped.
+        assert_eq!(groups, vec![vec![paths[0].clone(), paths[1].clone()]]);
+        Ok(())
+    }
+}
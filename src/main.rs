@@ -36,9 +36,26 @@ fn run() -> FIFResult<()> {
     let time = Instant::now();
     let arguments = Arguments::build()?;
 
+    // Restore the files moved to quarantine, instead of searching.
+    if let Some(manifest_path) = &arguments.restore {
+        return restore_quarantine(manifest_path);
+    }
+
+    // Undo the steps recorded in the journal, instead of searching.
+    if let Some(journal_path) = &arguments.undo {
+        return undo_journal(journal_path);
+    }
+
     // 1. Initial file collection
-    // Get useful (identical) and useless (non-identical) files.
-    let all_files: Vec<FileInfo> = get_all_files(&arguments)?;
+    // Get useful (identical) and useless (non-identical) files,
+    // walking the input directories or reading the list of files.
+    let all_files: Vec<FileInfo> = match &arguments.files_from {
+        Some(list_path) => get_listed_files(&arguments, list_path)?,
+        None => get_all_files(&arguments)?,
+    };
+
+    // Hard links to the same file are one logical file: they share their content.
+    let (all_files, num_hard_links) = collapse_hard_links(all_files);
 
     if arguments.verbose {
         eprintln!(
@@ -51,7 +68,7 @@ fn run() -> FIFResult<()> {
 
     // 2. Processing Pipeline (Successive Filtering)
 
-    // To skip useless files, 3 procedures will be performed:
+    // To skip useless files, 4 procedures will be performed (and optionally a 5th):
 
     // Procedure 1. Group files by <size> such that the key: (size, None);
     // Ignore filegroups containing only one file.
@@ -68,22 +85,83 @@ fn run() -> FIFResult<()> {
 
     // Procedure 2. Group files by <hash(first_bytes)> such that the key: (size, Some(hash(first_bytes)));
     // Ignore filegroups containing only one file.
-    let identical_bytes: Vec<GroupInfo> =
-        identical_size.get_identical_files(&arguments, Procedure::FirstBytes)?;
+    let mut identical_bytes: Vec<GroupInfo> = identical_size;
 
-    if arguments.verbose {
-        eprintln!(
-            "2. {:<43}: {:>10}, time_elapsed: {:?}",
-            Procedure::FirstBytes.description(),
-            identical_bytes.len(),
-            time.elapsed()
-        );
+    if arguments.first_bytes > 0 {
+        identical_bytes = identical_bytes.get_identical_files(&arguments, Procedure::FirstBytes)?;
+
+        if arguments.verbose {
+            eprintln!(
+                "2. {:<43}: {:>10}, time_elapsed: {:?}",
+                Procedure::FirstBytes.description(),
+                identical_bytes.len(),
+                time.elapsed()
+            );
+        }
     }
 
-    // Procedure 3. Group files by <hash(entire_file)> such that the key: (size, Some(hash(entire_file))).
+    // Procedure 3. Group files by <hash(last_bytes)> such that the key: (size, Some(hash(last_bytes)));
     // Ignore filegroups containing only one file.
-    let mut identical_hash: Vec<GroupInfo> =
-        identical_bytes.get_identical_files(&arguments, Procedure::EntireFile)?;
+    if arguments.last_bytes > 0 {
+        identical_bytes = identical_bytes.get_identical_files(&arguments, Procedure::LastBytes)?;
+
+        if arguments.verbose {
+            eprintln!(
+                "3. {:<43}: {:>10}, time_elapsed: {:?}",
+                Procedure::LastBytes.description(),
+                identical_bytes.len(),
+                time.elapsed()
+            );
+        }
+    }
+
+    // Optional rounds. Group files by <hash(prefix)> with prefixes of growing size,
+    // while the prefix is at most a quarter of the files of some remaining group.
+    if arguments.progressive {
+        let mut window: u64 = PROGRESSIVE_WINDOW;
+        while window <= arguments.first_bytes {
+            window = window.saturating_mul(PROGRESSIVE_GROWTH);
+        }
+
+        let mut round: usize = 1;
+        while identical_bytes
+            .iter()
+            .any(|group_info| group_info.takes_progressive_round(window))
+        {
+            identical_bytes = identical_bytes.get_identical_prefixes(&arguments, window)?;
+
+            // Survivors: files still compared after this round.
+            if arguments.verbose {
+                let num_files: usize = identical_bytes.iter().map(|g| g.num_file).sum();
+                eprintln!(
+                    "3.{round} {:<42}: {:>10}, time_elapsed: {:?}",
+                    format!(
+                        "Files with identical prefix of {}",
+                        format_size(window as usize)
+                    ),
+                    num_files,
+                    time.elapsed()
+                );
+            }
+
+            window = window.saturating_mul(PROGRESSIVE_GROWTH);
+            round += 1;
+        }
+    }
+
+    // Procedure 4. Group files by <hash(entire_file)> such that the key: (size, Some(hash(entire_file))).
+    // Ignore filegroups containing only one file.
+    // In quick mode, group files by <hash(sampled_blocks)> instead (probabilistic).
+    // With --lockstep, compare the files byte by byte instead, without hashing them all.
+    let final_procedure: Procedure = match arguments.quick {
+        Some(_) => Procedure::Sampled,
+        None if arguments.lockstep => Procedure::ByteByByte,
+        None => Procedure::EntireFile,
+    };
+    let mut identical_hash: Vec<GroupInfo> = match final_procedure {
+        Procedure::ByteByByte => identical_bytes.get_lockstep_files(&arguments)?,
+        _ => identical_bytes.get_identical_files(&arguments, final_procedure)?,
+    };
 
     // For testing purposes only:
     // https://rustlang.github.io/asyncbook/01_getting_started/04_async_await_primer.html
@@ -91,19 +169,49 @@ fn run() -> FIFResult<()> {
 
     if arguments.verbose {
         eprintln!(
-            "3. {:<43}: {:>10}, time_elapsed: {:?}",
-            Procedure::EntireFile.description(),
+            "4. {:<43}: {:>10}, time_elapsed: {:?}",
+            final_procedure.description(),
             identical_hash.len(),
             time.elapsed()
         );
     }
 
+    // Procedure 5. Split groups whose files differ, comparing them byte by byte.
+    // Enabled by default before any action on the files (already done with --lockstep).
+    if arguments.verify_is_enabled() && final_procedure != Procedure::ByteByByte {
+        identical_hash = identical_hash.get_verified_files(&arguments, final_procedure)?;
+
+        if arguments.verbose {
+            eprintln!(
+                "5. {:<43}: {:>10}, time_elapsed: {:?}",
+                Procedure::ByteByByte.description(),
+                identical_hash.len(),
+                time.elapsed()
+            );
+        }
+    }
+
+    // Hide the groups made up only of files of the reference directories.
+    if !arguments.reference_dir.is_empty() {
+        identical_hash.retain(|group_info| !group_info.is_reference_only(&arguments));
+    }
+
     // 3. Post-processing and Summary
     // Sort the list of identical files.
     identical_hash.sort_identical_files(&arguments);
 
+    // Review the identical files interactively, instead of printing them.
+    if arguments.interactive {
+        return review_groups(&identical_hash, &arguments);
+    }
+
+    // Review the identical files with a line-based prompt, instead of printing them.
+    if arguments.prompt {
+        return prompt_groups(&identical_hash, &arguments);
+    }
+
     // Print the identical files and the summary information.
-    TotalInfo::get_summary(&identical_hash, &arguments, all_files.len())
+    TotalInfo::get_summary(&identical_hash, &arguments, all_files.len(), num_hard_links)
         .print_summary(&arguments)?;
 
     // Export identical file information simultaneously to CSV and/or XLSX format.
@@ -131,14 +239,14 @@ fn run() -> FIFResult<()> {
     // We destructure the returning tuple to handle errors individually.
     let (csv_result, xlsx_result) = rayon::join(
         || -> FIFResult<()> {
-            if let Some(dir_path) = arguments.csv_dir {
-                identical_hash.export_to_csv(dir_path)?;
+            if let Some(dir_path) = &arguments.csv_dir {
+                identical_hash.export_to_csv(dir_path.clone())?;
             }
             Ok(())
         },
         || -> FIFResult<()> {
-            if let Some(dir_path) = arguments.xlsx_dir {
-                identical_hash.export_to_xlsx(dir_path)?;
+            if let Some(dir_path) = &arguments.xlsx_dir {
+                identical_hash.export_to_xlsx(dir_path.clone())?;
             }
             Ok(())
         },
@@ -148,8 +256,23 @@ fn run() -> FIFResult<()> {
     csv_result?;
     xlsx_result?;
 
+    // 5. Optional action on the duplicates.
+    // The dry-run preview is always printed before any file is changed.
+    if let Some(action) = arguments.action {
+        let plans: Vec<PlanInfo> = identical_hash.get_plans(&arguments);
+        plans.print_preview(&arguments, action)?;
+        plans
+            .apply_plans(&arguments, action)?
+            .print_summary(&arguments)?;
+    }
+
     if arguments.time {
-        println!("Total Execution Time: {:?}", time.elapsed());
+        let execution_time = format!("Total Execution Time: {:?}", time.elapsed());
+        match arguments.result_format {
+            // Keep the script valid: the time is written as a comment.
+            ResultFormat::Shell => write_comment(&mut std::io::stdout(), &execution_time)?,
+            _ => println!("{execution_time}"),
+        }
     }
 
     Ok(())
@@ -0,0 +1,296 @@
+use crate::{
+    Action, ActionInfo, Arguments, FIFResult, GroupInfo, Journal, PlanInfo, display_size,
+    write_manifest,
+};
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+/// Answer given for a group of identical files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// Keep the files with these indexes (starting at 0) and act on the others.
+    Keep(Vec<usize>),
+    /// Keep all files of the group.
+    All,
+    /// Go to the next group without changing any file.
+    Skip,
+    /// Stop reviewing the groups.
+    Quit,
+}
+
+/// Parses the answer typed by the user.
+///
+/// Files are numbered from 1: "1,3" keeps the first and third files,
+/// "2-4" keeps the second to fourth files.
+pub fn parse_selection(input: &str, num_files: usize) -> Result<Selection, String> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "all" | "a" => return Ok(Selection::All),
+        "skip" | "s" | "" => return Ok(Selection::Skip),
+        "quit" | "q" => return Ok(Selection::Quit),
+        _ => {}
+    }
+
+    let mut indexes: Vec<usize> = Vec::new();
+
+    for item in input.split([',', ' ']).filter(|item| !item.is_empty()) {
+        let (first, last) = match item.split_once('-') {
+            Some((first, last)) => (
+                parse_number(first, num_files)?,
+                parse_number(last, num_files)?,
+            ),
+            None => {
+                let number = parse_number(item, num_files)?;
+                (number, number)
+            }
+        };
+
+        if first > last {
+            return Err(format!("invalid range: {item}"));
+        }
+
+        indexes.extend((first..=last).map(|number| number - 1));
+    }
+
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    match indexes.len() {
+        0 => Err("select at least one file to keep".to_string()),
+        n if n == num_files => Ok(Selection::All),
+        _ => Ok(Selection::Keep(indexes)),
+    }
+}
+
+/// Parses a file number between 1 and `num_files`.
+fn parse_number(text: &str, num_files: usize) -> Result<usize, String> {
+    match text.trim().parse::<usize>() {
+        Ok(number) if (1..=num_files).contains(&number) => Ok(number),
+        _ => Err(format!(
+            "expected a number between 1 and {num_files}: {text}"
+        )),
+    }
+}
+
+/// Reviews the identical files one group at a time, in the terminal.
+///
+/// For each group, the user types the numbers of the files to keep;
+/// the action (--action, delete by default) is applied to the other files
+/// and recorded in the journal.
+/// Files in reference directories are always kept.
+pub fn prompt_groups(groups: &[GroupInfo], arguments: &Arguments) -> FIFResult<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    prompt_groups_with(groups, arguments, &mut stdin.lock(), &mut stdout)?;
+    Ok(())
+}
+
+/// Reviews the groups, reading the answers from `reader`.
+pub fn prompt_groups_with(
+    groups: &[GroupInfo],
+    arguments: &Arguments,
+    reader: &mut dyn BufRead,
+    write: &mut dyn Write,
+) -> FIFResult<ActionInfo> {
+    // Answering the prompt replaces --execute.
+    let mut arguments = arguments.clone();
+    arguments.execute = true;
+
+    // Without --action, the other files are deleted.
+    let action: Action = arguments.action.unwrap_or(Action::Delete);
+
+    let mut action_info = ActionInfo::new(&arguments, action);
+    let mut journal: Option<Journal> = None;
+
+    'groups: for (number, group_info) in groups.iter().enumerate() {
+        let references: Vec<bool> = group_info.get_references(&arguments);
+
+        writeln!(write, "[{}/{}]", number + 1, groups.len())?;
+        for (index, path) in group_info.paths.iter().enumerate() {
+            let note = if references[index] {
+                " (reference)"
+            } else {
+                ""
+            };
+            writeln!(write, "  [{}] {path:?}{note}", index + 1)?;
+        }
+        group_info.print_personal_details(&arguments, write)?;
+
+        let selection = loop {
+            write!(
+                write,
+                "Files to keep (e.g. 1,3 or 2-4), all, skip or quit: "
+            )?;
+            write.flush()?;
+
+            let mut input = String::new();
+            if reader.read_line(&mut input)? == 0 {
+                // End of input: stop, as with quit.
+                writeln!(write)?;
+                break 'groups;
+            }
+
+            match parse_selection(&input, group_info.paths.len()) {
+                Ok(selection) => break selection,
+                Err(message) => writeln!(write, "{message}")?,
+            }
+        };
+
+        let mut keep: Vec<usize> = match selection {
+            Selection::Keep(keep) => keep,
+            Selection::All | Selection::Skip => {
+                writeln!(write, "No file was changed.\n")?;
+                continue;
+            }
+            Selection::Quit => break,
+        };
+
+        // Reference files are kept first: one of them is recorded as the file kept.
+        let mut reference_keep: Vec<usize> = (0..references.len())
+            .filter(|&index| references[index])
+            .collect();
+        keep.retain(|index| !references[*index]);
+        reference_keep.append(&mut keep);
+        let keep = reference_keep;
+
+        let duplicates: Vec<PathBuf> = group_info
+            .paths
+            .iter()
+            .enumerate()
+            .filter(|(index, _path)| !keep.contains(index))
+            .map(|(_index, path)| path.clone())
+            .collect();
+
+        // The first file kept is recorded in the journal, to undo the action.
+        let plan_info = PlanInfo {
+            key: group_info.key.clone(),
+            keeper: group_info.paths[keep[0]].clone(),
+            sum_size: group_info.key.size * duplicates.len(),
+            duplicates,
+        };
+
+        let journal = match &mut journal {
+            Some(journal) => journal,
+            None => journal.insert(Journal::open(&arguments.journal)?),
+        };
+
+        // The manifest is written before any file is moved.
+        if let (Action::Quarantine, Some(quarantine_dir)) = (action, &arguments.quarantine_dir) {
+            write_manifest(std::slice::from_ref(&plan_info), quarantine_dir)?;
+        }
+
+        let reclaimed_before = action_info.reclaimed_size;
+        action_info.num_duplicates += plan_info.duplicates.len();
+        action_info.total_size += plan_info.sum_size;
+        plan_info.apply(&arguments, action, journal, &mut action_info);
+
+        for (index, path) in group_info.paths.iter().enumerate() {
+            let mark = if keep.contains(&index) { '+' } else { '-' };
+            writeln!(write, "  [{mark}] {path:?}")?;
+        }
+        writeln!(
+            write,
+            "Reclaimed: {}\n",
+            display_size(action_info.reclaimed_size - reclaimed_before, &arguments)
+        )?;
+    }
+
+    action_info.print_summary(&arguments)?;
+
+    Ok(action_info)
+}
+
+#[cfg(test)]
+mod tests_prompt {
+    use super::*;
+    use crate::Key;
+    use clap::Parser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1,3", 4), Ok(Selection::Keep(vec![0, 2])));
+        assert_eq!(parse_selection(" 2-3 ", 4), Ok(Selection::Keep(vec![1, 2])));
+        assert_eq!(parse_selection("1-4", 4), Ok(Selection::All));
+        assert_eq!(parse_selection("ALL", 4), Ok(Selection::All));
+        assert_eq!(parse_selection("skip", 4), Ok(Selection::Skip));
+        assert_eq!(parse_selection("q", 4), Ok(Selection::Quit));
+        assert!(parse_selection("5", 4).is_err());
+        assert!(parse_selection("3-1", 4).is_err());
+        assert!(parse_selection("x", 4).is_err());
+    }
+
+    #[test]
+    fn test_prompt_groups() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths: Vec<PathBuf> = (1..=3)
+            .map(|i| dir.path().join(format!("file_{i}")))
+            .collect();
+        for path in &paths {
+            fs::write(path, b"same content")?;
+        }
+
+        let groups = vec![GroupInfo {
+            paths: paths.clone(),
+            key: Key::new(12, None)?,
+            num_file: 3,
+            sum_size: 36,
+        }];
+
+        let mut arguments = Arguments::parse_from(["find-identical-files"]);
+        arguments.journal = dir.path().join("journal.jsonl");
+
+        // An invalid answer is asked again.
+        let mut reader = "9\n2\n".as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        let action_info = prompt_groups_with(&groups, &arguments, &mut reader, &mut output)?;
+
+        assert_eq!(action_info.num_processed, 2);
+        assert_eq!(action_info.reclaimed_size, 24);
+        assert!(!paths[0].exists());
+        assert!(paths[1].exists());
+        assert!(!paths[2].exists());
+
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains("expected a number between 1 and 3: 9"));
+        assert!(output.contains("Reclaimed: 24 bytes"));
+        Ok(())
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_prompt_groups_with_action() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths: Vec<PathBuf> = (1..=2)
+            .map(|i| dir.path().join(format!("file_{i}")))
+            .collect();
+        for path in &paths {
+            fs::write(path, b"same content")?;
+        }
+
+        let groups = vec![GroupInfo {
+            paths: paths.clone(),
+            key: Key::new(12, None)?,
+            num_file: 2,
+            sum_size: 24,
+        }];
+
+        let mut arguments =
+            Arguments::parse_from(["find-identical-files", "--prompt", "-A", "symlink"]);
+        arguments.journal = dir.path().join("journal.jsonl");
+
+        let mut reader = "1\n".as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        let action_info = prompt_groups_with(&groups, &arguments, &mut reader, &mut output)?;
+
+        // The duplicate is replaced with a symbolic link, not deleted.
+        assert_eq!(action_info.action, Action::Symlink);
+        assert_eq!(action_info.num_processed, 1);
+        assert!(paths[1].is_symlink());
+        assert_eq!(fs::read(&paths[1])?, b"same content");
+        Ok(())
+    }
+}
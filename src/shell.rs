@@ -0,0 +1,245 @@
+use crate::{
+    Action, Arguments, FIFResult, PlanInfo, get_thousands_separator, quarantine_path,
+    split_and_insert, symlink_target,
+};
+use std::{fmt::Write as FmtWrite, io::Write, path::Path};
+
+/// First lines of the generated shell script.
+pub const SHELL_HEADER: &str = "\
+#!/bin/sh
+# Generated by find-identical-files.
+# Review the commands below before running this script.
+set -eu
+
+";
+
+/// Quotes a path for a POSIX shell.
+///
+/// Text is enclosed in single quotes. Bytes that are not valid UTF-8 and control
+/// characters (except newline) are written as octal escapes: `"$(printf '\NNN')"`.
+pub fn shell_quote(path: &Path) -> String {
+    let mut quoted = String::new();
+    let mut escaped = String::new();
+    let mut literal = String::new();
+
+    // Flush the pending literal text or escaped bytes as a quoted segment.
+    let flush_literal = |quoted: &mut String, literal: &mut String| {
+        if !literal.is_empty() {
+            quoted.push('\'');
+            quoted.push_str(&literal.replace('\'', r"'\''"));
+            quoted.push('\'');
+            literal.clear();
+        }
+    };
+    let flush_escaped = |quoted: &mut String, escaped: &mut String| {
+        if !escaped.is_empty() {
+            quoted.push_str("\"$(printf '");
+            quoted.push_str(escaped);
+            quoted.push_str("')\"");
+            escaped.clear();
+        }
+    };
+
+    for chunk in path_bytes(path).utf8_chunks() {
+        for character in chunk.valid().chars() {
+            if character.is_control() && character != '\n' {
+                flush_literal(&mut quoted, &mut literal);
+                let mut buffer = [0; 4];
+                for byte in character.encode_utf8(&mut buffer).bytes() {
+                    let _ = write!(escaped, "\\{byte:03o}");
+                }
+            } else {
+                flush_escaped(&mut quoted, &mut escaped);
+                literal.push(character);
+            }
+        }
+
+        if !chunk.invalid().is_empty() {
+            flush_literal(&mut quoted, &mut literal);
+            for byte in chunk.invalid() {
+                let _ = write!(escaped, "\\{byte:03o}");
+            }
+        }
+    }
+
+    flush_literal(&mut quoted, &mut literal);
+    flush_escaped(&mut quoted, &mut escaped);
+
+    if quoted.is_empty() {
+        quoted.push_str("''");
+    }
+
+    quoted
+}
+
+/// The raw bytes of a path.
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> &[u8] {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes()
+}
+
+/// The raw bytes of a path (lossy conversion outside Unix).
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> &[u8] {
+    path.to_str().map(str::as_bytes).unwrap_or_default()
+}
+
+/// Writes the text as shell comments.
+pub fn write_comment(write: &mut dyn Write, text: &str) -> FIFResult<()> {
+    for line in text.lines() {
+        writeln!(write, "# {line}")?;
+    }
+    Ok(())
+}
+
+/// Writes the commands that apply the action to the duplicates of a plan.
+///
+/// Each group starts with comments: the hash, the sizes and the file kept.
+pub fn write_commands(
+    plan_info: &PlanInfo,
+    arguments: &Arguments,
+    action: Action,
+    write: &mut dyn Write,
+) -> FIFResult<()> {
+    let thousands_separator: char = get_thousands_separator();
+    let keeper = shell_quote(&plan_info.keeper);
+
+    writeln!(
+        write,
+        "# Hash: {}",
+        plan_info.key.hash.clone().unwrap_or_default()
+    )?;
+    writeln!(
+        write,
+        "# Size of individual file: {} bytes",
+        split_and_insert(plan_info.key.size, thousands_separator)?
+    )?;
+    // Comments may span several lines if the path contains a newline.
+    write_comment(write, &format!("Keep: {keeper}"))?;
+
+    for duplicate in &plan_info.duplicates {
+        let quoted = shell_quote(duplicate);
+
+        match action {
+            Action::Delete => writeln!(write, "rm -- {quoted}")?,
+            Action::HardLink => writeln!(write, "ln -f -- {keeper} {quoted}")?,
+            // GNU cp: fails instead of copying if reflinks are not supported.
+            Action::Reflink => writeln!(write, "cp --reflink=always -- {keeper} {quoted}")?,
+            Action::Symlink => {
+                let target = symlink_target(&plan_info.keeper, duplicate, arguments.symlink_type)?;
+                writeln!(write, "ln -sf -- {} {quoted}", shell_quote(&target))?;
+            }
+            Action::Quarantine => match &arguments.quarantine_dir {
+                Some(quarantine_dir) => {
+                    let quarantine = quarantine_path(duplicate, quarantine_dir)?;
+                    if let Some(parent) = quarantine.parent() {
+                        writeln!(write, "mkdir -p -- {}", shell_quote(parent))?;
+                    }
+                    // POSIX mv has no -n: never overwrite a file already in quarantine.
+                    let quarantine = shell_quote(&quarantine);
+                    writeln!(write, "[ -e {quarantine} ] || mv -- {quoted} {quarantine}")?;
+                }
+                None => write_comment(
+                    write,
+                    &format!("Skipped: {quoted} (no quarantine directory)"),
+                )?,
+            },
+        }
+    }
+
+    writeln!(
+        write,
+        "# Sum of duplicate sizes: {} bytes\n",
+        split_and_insert(plan_info.sum_size, thousands_separator)?
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_shell {
+    use super::*;
+    use crate::Key;
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote(Path::new("/a/b c")), "'/a/b c'");
+        assert_eq!(shell_quote(Path::new("it's")), r"'it'\''s'");
+        assert_eq!(shell_quote(Path::new("-rf $HOME")), "'-rf $HOME'");
+        assert_eq!(
+            shell_quote(Path::new("a\tb")),
+            "'a'\"$(printf '\\011')\"'b'"
+        );
+        assert_eq!(shell_quote(Path::new("line\nbreak")), "'line\nbreak'");
+        assert_eq!(shell_quote(Path::new("")), "''");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_quote_non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // Latin-1 encoded "café.txt"
+        let path = Path::new(OsStr::from_bytes(b"caf\xe9.txt"));
+        assert_eq!(shell_quote(path), "'caf'\"$(printf '\\351')\"'.txt'");
+    }
+
+    #[test]
+    fn test_write_commands() -> FIFResult<()> {
+        let plan_info = PlanInfo {
+            key: Key {
+                size: 1000,
+                hash: Some("abc".to_string()),
+            },
+            keeper: PathBuf::from("/data/keep me"),
+            duplicates: vec![PathBuf::from("/data/copy"), PathBuf::from("/data/it's")],
+            sum_size: 2000,
+        };
+        let arguments = Arguments::parse_from(["find-identical-files"]);
+
+        let mut output: Vec<u8> = Vec::new();
+        write_commands(&plan_info, &arguments, Action::HardLink, &mut output)?;
+        let output = String::from_utf8_lossy(&output);
+
+        assert!(output.contains("# Keep: '/data/keep me'"));
+        assert!(output.contains("ln -f -- '/data/keep me' '/data/copy'\n"));
+        assert!(output.contains(r"ln -f -- '/data/keep me' '/data/it'\''s'"));
+        Ok(())
+    }
+    #[test]
+    fn test_write_quarantine_commands() -> FIFResult<()> {
+        let dir = tempfile::TempDir::new()?;
+        let root = std::fs::canonicalize(dir.path())?;
+        let quarantine_dir = root.join("quarantine");
+        std::fs::create_dir(&quarantine_dir)?;
+
+        let plan_info = PlanInfo {
+            key: Key::new(4, None)?,
+            keeper: root.join("keep"),
+            duplicates: vec![root.join("copy")],
+            sum_size: 4,
+        };
+        let arguments = Arguments::parse_from([
+            "find-identical-files".as_ref(),
+            "--quarantine_dir".as_ref(),
+            quarantine_dir.as_os_str(),
+        ]);
+
+        let mut output: Vec<u8> = Vec::new();
+        write_commands(&plan_info, &arguments, Action::Quarantine, &mut output)?;
+        let output = String::from_utf8_lossy(&output);
+
+        // POSIX mv has no -n option: the script tests the destination instead.
+        let copy = shell_quote(&root.join("copy"));
+        let quarantine = shell_quote(&quarantine_path(&root.join("copy"), &quarantine_dir)?);
+        assert!(output.contains(&format!(
+            "[ -e {quarantine} ] || mv -- {copy} {quarantine}\n"
+        )));
+        assert!(!output.contains("mv -n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    Action, FIFResult, KeepRule, add_thousands_separator,
+    args::{Arguments, ResultFormat::*},
+    display_size, write_comment,
+};
+use serde::Serialize;
+use std::io;
+
+/// Summarize the result of an action applied to the identical files
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionInfo {
+    /// Action applied to the duplicates
+    #[serde(rename = "Action")]
+    pub action: Action,
+    /// Rule used to choose the file to keep
+    #[serde(rename = "Keep rule")]
+    pub keep: KeepRule,
+    /// If true, no file was changed
+    #[serde(rename = "Dry run")]
+    pub dry_run: bool,
+    /// Number of duplicates found (all files except the keepers)
+    #[serde(rename = "Number of duplicate files")]
+    pub num_duplicates: usize,
+    /// Number of duplicates successfully processed
+    #[serde(rename = "Number of processed files")]
+    pub num_processed: usize,
+    /// Number of duplicates skipped (e.g. stored on another file system)
+    #[serde(rename = "Number of skipped files")]
+    pub num_skipped: usize,
+    /// Number of duplicates whose processing failed
+    #[serde(rename = "Number of failed files")]
+    pub num_failed: usize,
+    /// Total size of the duplicates
+    #[serde(
+        rename = "Total size of duplicate files",
+        serialize_with = "add_thousands_separator"
+    )]
+    pub total_size: usize,
+    /// Total size of the duplicates successfully processed whose space was freed
+    /// (files with other hard links are not counted)
+    #[serde(rename = "Reclaimed size", serialize_with = "add_thousands_separator")]
+    pub reclaimed_size: usize,
+}
+
+impl ActionInfo {
+    /// Creates an empty summary for the given action.
+    pub fn new(arguments: &Arguments, action: Action) -> Self {
+        ActionInfo {
+            action,
+            keep: arguments.keep,
+            dry_run: !arguments.execute,
+            num_duplicates: 0,
+            num_processed: 0,
+            num_skipped: 0,
+            num_failed: 0,
+            total_size: 0,
+            reclaimed_size: 0,
+        }
+    }
+
+    /// Print the action summary.
+    pub fn print_summary(&self, arguments: &Arguments) -> FIFResult<()> {
+        match &arguments.result_format {
+            Json => {
+                let serialized = serde_json::to_string_pretty(&self)?;
+                println!("{serialized}\n");
+            }
+            Yaml => {
+                let serialized = serde_yaml::to_string(&self)?;
+                println!("{serialized}");
+            }
+            Personal => {
+                println!("Action: {}", self.action);
+                println!("Keep rule: {}", self.keep);
+                println!("Number of duplicate files: {}", self.num_duplicates);
+                println!(
+                    "Total size of duplicate files: {}",
+                    display_size(self.total_size, arguments)
+                );
+
+                if self.dry_run {
+                    println!("Dry run: no file was changed. Use --execute to apply the action.\n");
+                } else {
+                    println!("Number of processed files: {}", self.num_processed);
+                    println!("Number of skipped files: {}", self.num_skipped);
+                    println!("Number of failed files: {}", self.num_failed);
+                    println!(
+                        "Reclaimed size: {}\n",
+                        display_size(self.reclaimed_size, arguments)
+                    );
+                }
+            }
+            Shell => {
+                // Keep the script valid: the summary is written as comments.
+                let serialized = serde_yaml::to_string(&self)?;
+                write_comment(&mut io::stdout(), &serialized)?;
+            }
+        }
+        Ok(())
+    }
+}
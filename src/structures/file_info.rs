@@ -1,5 +1,5 @@
 use crate::{Arguments, GroupInfo, Key, Procedure};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, hash_map::Entry};
 use rayon::prelude::*;
 use std::path::PathBuf;
 
@@ -11,6 +11,47 @@ pub struct FileInfo {
 
     /// File Path
     pub path: PathBuf,
+
+    /// Device and inode numbers (Unix only).
+    ///
+    /// Paths with the same numbers are hard links to the same file.
+    pub file_id: Option<(u64, u64)>,
+}
+
+/// Collapses the paths that are hard links to the same file into one logical file.
+///
+/// The path kept is the first in alphabetical order, so the choice is deterministic;
+/// a symbolic link (--follow_symlinks) is only kept if its target was not found.
+/// Returns the files and the number of paths removed (hard links already sharing their content).
+pub fn collapse_hard_links(all_files: Vec<FileInfo>) -> (Vec<FileInfo>, usize) {
+    let mut files: Vec<FileInfo> = Vec::with_capacity(all_files.len());
+    let mut positions: HashMap<(u64, u64), usize> = HashMap::new();
+    let mut num_hard_links: usize = 0;
+
+    for file_info in all_files {
+        let Some(file_id) = file_info.file_id else {
+            files.push(file_info);
+            continue;
+        };
+
+        match positions.entry(file_id) {
+            Entry::Occupied(entry) => {
+                num_hard_links += 1;
+                let kept = &mut files[*entry.get()];
+                if (file_info.path.is_symlink(), &file_info.path)
+                    < (kept.path.is_symlink(), &kept.path)
+                {
+                    *kept = file_info;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(files.len());
+                files.push(file_info);
+            }
+        }
+    }
+
+    (files, num_hard_links)
 }
 
 pub trait FileExtension {
@@ -57,3 +98,39 @@ impl FileExtension for [FileInfo] {
         grouped_files
     }
 }
+
+#[cfg(test)]
+mod tests_file_info {
+    use super::*;
+    use crate::FIFResult;
+
+    #[test]
+    fn test_collapse_hard_links() -> FIFResult<()> {
+        let file = |path: &str, file_id: Option<(u64, u64)>| -> FIFResult<FileInfo> {
+            Ok(FileInfo {
+                key: Key::new(10, None)?,
+                path: PathBuf::from(path),
+                file_id,
+            })
+        };
+
+        let all_files = vec![
+            file("/b/link", Some((1, 7)))?,
+            file("/a/original", Some((1, 7)))?,
+            file("/c/other", Some((1, 8)))?,
+            // Same inode number on another device: a different file.
+            file("/d/other", Some((2, 7)))?,
+            file("/e/unknown", None)?,
+        ];
+
+        let (files, num_hard_links) = collapse_hard_links(all_files);
+        let paths: Vec<&str> = files
+            .iter()
+            .filter_map(|file_info| file_info.path.to_str())
+            .collect();
+
+        assert_eq!(num_hard_links, 1);
+        assert_eq!(paths, ["/a/original", "/c/other", "/d/other", "/e/unknown"]);
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
-    CSV_FILENAME, FIFResult, FileExtension, FileInfo, Key, PathBufExtension, PathInfo, Procedure,
-    TotalInfo, XLSX_FILENAME, add_thousands_separator,
+    Action, CSV_FILENAME, FIFResult, FileExtension, FileInfo, Key, PROGRESSIVE_MAX_FRACTION,
+    PathBufExtension, PathInfo, PlanInfo, Procedure, SHELL_HEADER, TotalInfo, XLSX_FILENAME,
+    absolute_path, add_thousands_separator,
     args::{Arguments, ResultFormat::*},
-    get_thousands_separator, my_print, split_and_insert, write_xlsx,
+    display_size, my_print, partition_by_content, remove_thousands_separator,
+    traits::Colors,
+    write_commands, write_xlsx,
 };
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -26,20 +29,19 @@ pub struct GroupInfo {
     /// Sum of individual file sizes in this group
     #[serde(
         rename = "Sum of file sizes",
-        serialize_with = "add_thousands_separator"
+        serialize_with = "add_thousands_separator",
+        deserialize_with = "remove_thousands_separator"
     )]
     pub sum_size: usize,
 }
 
 impl GroupInfo {
-    /// Print GroupInfo fields in the chosen format (JSON, YAML, or Personal)
+    /// Print GroupInfo fields in the chosen format (JSON, YAML, Personal or Shell)
     pub fn print_formatted(
         &self,
         arguments: &Arguments,
         write: &mut Box<&mut dyn Write>,
     ) -> FIFResult<()> {
-        let thousands_separator: char = get_thousands_separator();
-
         match &arguments.result_format {
             Json => {
                 // Serialize GroupInfo to a JSON string.
@@ -53,24 +55,42 @@ impl GroupInfo {
             }
             Personal => {
                 writeln!(write, "Paths: {:#?}", self.paths)?;
-                writeln!(write, "Hash: {}", self.key.hash.clone().unwrap_or_default())?;
-                writeln!(write, "Number of identical files: {}", self.num_file)?;
-                writeln!(
-                    write,
-                    "Size of individual file: {} bytes",
-                    split_and_insert(self.key.size, thousands_separator)?
-                )?;
-                writeln!(
-                    write,
-                    "Sum of file sizes: {} bytes\n",
-                    split_and_insert(self.sum_size, thousands_separator)?
-                )?;
+                self.print_personal_details(arguments, &mut **write)?;
+            }
+            Shell => {
+                // Without --action, the script removes the duplicates.
+                let action = arguments.action.unwrap_or(Action::Delete);
+                if let Some(plan_info) = self.get_plan(arguments)? {
+                    write_commands(&plan_info, arguments, action, &mut **write)?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Print the Personal format fields that follow the paths (hash, number of files and sizes).
+    pub fn print_personal_details(
+        &self,
+        arguments: &Arguments,
+        write: &mut dyn Write,
+    ) -> FIFResult<()> {
+        writeln!(write, "Hash: {}", self.key.hash.clone().unwrap_or_default())?;
+        writeln!(write, "Number of identical files: {}", self.num_file)?;
+        writeln!(
+            write,
+            "Size of individual file: {}",
+            display_size(self.key.size, arguments)
+        )?;
+        writeln!(
+            write,
+            "Sum of file sizes: {}\n",
+            display_size(self.sum_size, arguments)
+        )?;
+
+        Ok(())
+    }
+
     /// Updates the hash for all files in the group based on the current Procedure.
     ///
     /// This runs in parallel using Rayon. If any file fails to be hashed (e.g.,
@@ -92,11 +112,123 @@ impl GroupInfo {
                         hash,
                     },
                     path: path.clone(),
+                    // Hard links were already collapsed while collecting the files.
+                    file_id: None,
                 })
             })
             .collect() // Magic of Rayon/Std: Collects Vec<Result> into Result<Vec>
     }
 
+    /// Splits the group into groups of files with identical bytes.
+    ///
+    /// The hash is kept: only a hash collision can split a group.
+    pub fn split_by_content(&self, arguments: &Arguments) -> FIFResult<Vec<GroupInfo>> {
+        let min_frequency: usize = arguments.min_frequency as usize;
+        let max_frequency: usize = arguments.max_frequency as usize;
+
+        let groups = partition_by_content(&self.paths)?
+            .into_iter()
+            .filter(|paths| {
+                Procedure::ByteByByte.is_valid_frequency(paths.len(), min_frequency, max_frequency)
+            })
+            .map(|paths| GroupInfo {
+                key: self.key.clone(),
+                num_file: paths.len(),
+                sum_size: self.key.size * paths.len(),
+                paths,
+            })
+            .collect();
+
+        Ok(groups)
+    }
+
+    /// Returns, for each path, true if the file is in a reference directory.
+    pub fn get_references(&self, arguments: &Arguments) -> Vec<bool> {
+        if arguments.reference_dir.is_empty() {
+            return vec![false; self.paths.len()];
+        }
+
+        let reference_dirs: Vec<PathBuf> = arguments.get_reference_dirs();
+
+        self.paths
+            .iter()
+            .map(|path| {
+                // A symbolic link is in the directory where it is found, not where it points.
+                let path = absolute_path(path).unwrap_or_else(|_| path.clone());
+                reference_dirs.iter().any(|dir| path.starts_with(dir))
+            })
+            .collect()
+    }
+
+    /// Returns true if the files are large enough to be compared by a prefix of `window` bytes.
+    pub fn takes_progressive_round(&self, window: u64) -> bool {
+        window.saturating_mul(PROGRESSIVE_MAX_FRACTION) <= self.key.size as u64
+    }
+
+    /// Returns true if all files of the group are in reference directories.
+    pub fn is_reference_only(&self, arguments: &Arguments) -> bool {
+        !arguments.reference_dir.is_empty()
+            && self
+                .get_references(arguments)
+                .into_iter()
+                .all(|reference| reference)
+    }
+
+    /// Returns the index of the file to keep according to the keep rule.
+    ///
+    /// If the group has reference files, the file kept is one of them.
+    pub fn select_keeper(
+        &self,
+        arguments: &Arguments,
+        references: &[bool],
+    ) -> FIFResult<Option<usize>> {
+        let has_reference: bool = references.contains(&true);
+
+        let candidates: Vec<usize> = (0..self.paths.len())
+            .filter(|&i| !has_reference || references[i])
+            .collect();
+
+        let paths: Vec<PathBuf> = candidates.iter().map(|&i| self.paths[i].clone()).collect();
+
+        let position = arguments
+            .keep
+            .select_keeper(&paths, &arguments.priority_dir)?;
+
+        Ok(position.map(|position| candidates[position]))
+    }
+
+    /// Choose the file to keep according to the keep rule.
+    ///
+    /// Files in reference directories are never part of the duplicates.
+    ///
+    /// Returns `None` if the group has no duplicates to act on.
+    pub fn get_plan(&self, arguments: &Arguments) -> FIFResult<Option<PlanInfo>> {
+        let references: Vec<bool> = self.get_references(arguments);
+
+        let Some(index) = self.select_keeper(arguments, &references)? else {
+            return Ok(None);
+        };
+
+        let duplicates: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .enumerate()
+            .filter(|(i, _path)| *i != index && !references[*i])
+            .map(|(_i, path)| path.clone())
+            .collect();
+
+        if duplicates.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(PlanInfo {
+            key: self.key.clone(),
+            keeper: self.paths[index].clone(),
+            sum_size: self.key.size * duplicates.len(),
+            duplicates,
+        }))
+    }
+
     /// Convert [`GroupInfo`] to a flat vector of [`PathInfo`]
     pub fn flatten(&self) -> Vec<PathInfo> {
         self.paths
@@ -120,6 +252,36 @@ pub trait GroupExtension {
         procedure: Procedure,
     ) -> FIFResult<Vec<GroupInfo>>;
 
+    /// Split the groups by content, reading their files block by block in lockstep.
+    ///
+    /// Replaces the entire file hash: only one file per group is hashed, for the report.
+    fn get_lockstep_files(&self, arguments: &Arguments) -> FIFResult<Vec<GroupInfo>>;
+
+    /// Split the groups whose files differ, comparing them byte by byte (see --verify).
+    ///
+    /// After quick mode, the sampled hash is replaced with the entire file hash:
+    /// it is recorded in the journal to check the file kept before an undo.
+    fn get_verified_files(
+        &self,
+        arguments: &Arguments,
+        final_procedure: Procedure,
+    ) -> FIFResult<Vec<GroupInfo>>;
+
+    /// Set the hash of each group to the entire file hash of its first file.
+    ///
+    /// All files of the group are known to be identical: only one is hashed.
+    fn get_entire_file_hashes(&self, arguments: &Arguments) -> FIFResult<Vec<GroupInfo>>;
+
+    /// Split the groups by the hash of the first `window` bytes of their files.
+    ///
+    /// Only groups of files at least `PROGRESSIVE_MAX_FRACTION` times larger than the window
+    /// take part; the others are kept unchanged: their entire content is hashed later.
+    fn get_identical_prefixes(
+        &self,
+        arguments: &Arguments,
+        window: u64,
+    ) -> FIFResult<Vec<GroupInfo>>;
+
     /// Sort the list of identical files based on user arguments.
     fn sort_identical_files(&mut self, arguments: &Arguments);
 
@@ -127,7 +289,19 @@ pub trait GroupExtension {
     fn print_identical_files(&self, arguments: &Arguments) -> FIFResult<()>;
 
     /// Calculate total statistics (count, size, etc.)
-    fn get_total_info(&self, arguments: &Arguments, total_num_files: usize) -> TotalInfo;
+    ///
+    /// Hard links to the same file were collapsed and are counted apart.
+    fn get_total_info(
+        &self,
+        arguments: &Arguments,
+        total_num_files: usize,
+        total_num_hard_links: usize,
+    ) -> TotalInfo;
+
+    /// Choose the file to keep in each group and list the duplicates to act on.
+    ///
+    /// Groups whose keeper cannot be chosen (e.g. a file removed since the search) are skipped.
+    fn get_plans(&self, arguments: &Arguments) -> Vec<PlanInfo>;
 
     /// Convert Vec<[`GroupInfo`]> to Vec<[`PathInfo`]> for exporting
     fn get_path_info(&self) -> Vec<PathInfo>;
@@ -155,11 +329,23 @@ impl GroupExtension for [GroupInfo] {
                 Vec::new,
                 // 2. Folding: Process each group and accumulate results locally
                 |mut local_accumulator, group_info| {
-                    // Update hashes for the current group (short-circuits on Err)
-                    let updated_files = group_info.update_hash(arguments, procedure)?;
-
-                    // Group files based on the new hashes
-                    let new_subgroups = updated_files.get_grouped_files(arguments, procedure);
+                    let new_subgroups = match procedure {
+                        // Compare the files byte by byte (short-circuits on Err)
+                        Procedure::ByteByByte => group_info.split_by_content(arguments)?,
+                        // The first bytes already covered the entire file
+                        Procedure::LastBytes
+                            if group_info.key.size as u64 <= arguments.first_bytes =>
+                        {
+                            vec![group_info.clone()]
+                        }
+                        _ => {
+                            // Update hashes for the current group (short-circuits on Err)
+                            let updated_files = group_info.update_hash(arguments, procedure)?;
+
+                            // Group files based on the new hashes
+                            updated_files.get_grouped_files(arguments, procedure)
+                        }
+                    };
 
                     // Append subgroups to the local thread vector
                     local_accumulator.extend(new_subgroups);
@@ -178,6 +364,63 @@ impl GroupExtension for [GroupInfo] {
             )
     }
 
+    fn get_lockstep_files(&self, arguments: &Arguments) -> FIFResult<Vec<GroupInfo>> {
+        let groups: Vec<GroupInfo> = self
+            .par_iter()
+            .map(|group_info| group_info.split_by_content(arguments))
+            .collect::<FIFResult<Vec<Vec<GroupInfo>>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        groups.get_entire_file_hashes(arguments)
+    }
+
+    fn get_verified_files(
+        &self,
+        arguments: &Arguments,
+        final_procedure: Procedure,
+    ) -> FIFResult<Vec<GroupInfo>> {
+        let groups = self.get_identical_files(arguments, Procedure::ByteByByte)?;
+
+        match final_procedure {
+            Procedure::Sampled => groups.get_entire_file_hashes(arguments),
+            _ => Ok(groups),
+        }
+    }
+
+    fn get_entire_file_hashes(&self, arguments: &Arguments) -> FIFResult<Vec<GroupInfo>> {
+        self.par_iter()
+            .map(|group_info| {
+                // All files of the group are identical: the first one gives the hash.
+                let mut group_info = group_info.clone();
+                let hash = group_info.paths[0].get_hash(arguments, Procedure::EntireFile)?;
+                group_info.key.set_hash(hash);
+                Ok(group_info)
+            })
+            .collect()
+    }
+
+    fn get_identical_prefixes(
+        &self,
+        arguments: &Arguments,
+        window: u64,
+    ) -> FIFResult<Vec<GroupInfo>> {
+        let (larger, mut smaller): (Vec<GroupInfo>, Vec<GroupInfo>) = self
+            .iter()
+            .cloned()
+            .partition(|group_info| group_info.takes_progressive_round(window));
+
+        // A round is a first bytes stage with a larger window.
+        let mut round_arguments = arguments.clone();
+        round_arguments.first_bytes = window;
+
+        let mut groups = larger.get_identical_files(&round_arguments, Procedure::FirstBytes)?;
+        groups.append(&mut smaller);
+
+        Ok(groups)
+    }
+
     fn sort_identical_files(&mut self, arguments: &Arguments) {
         if arguments.sort {
             // Sort by number of identical files and then by (file size, hash).
@@ -212,11 +455,20 @@ impl GroupExtension for [GroupInfo] {
             .flatten()
             .collect();
 
+        if matches!(arguments.result_format, Shell) {
+            my_print(SHELL_HEADER.as_bytes())?;
+        }
+
         my_print(&all_buffer)?;
         Ok(())
     }
 
-    fn get_total_info(&self, arguments: &Arguments, total_num_files: usize) -> TotalInfo {
+    fn get_total_info(
+        &self,
+        arguments: &Arguments,
+        total_num_files: usize,
+        total_num_hard_links: usize,
+    ) -> TotalInfo {
         // Takes two closures and potentially runs them in parallel.
         let (total_num_identical, total_size) = rayon::join(
             || self.par_iter().map(|group_info| group_info.num_file).sum(),
@@ -242,13 +494,36 @@ impl GroupExtension for [GroupInfo] {
 
         TotalInfo {
             algorithm: arguments.algorithm,
+            comparison: arguments.get_comparison().to_string(),
             total_num_files,
+            total_num_hard_links,
             total_num_identical,
             total_num_hashes: self.len(),
             total_size,
         }
     }
 
+    fn get_plans(&self, arguments: &Arguments) -> Vec<PlanInfo> {
+        // Keep the order of the sorted groups in the preview.
+        let plans: Vec<Option<PlanInfo>> = self
+            .par_iter()
+            .map(|group_info| match group_info.get_plan(arguments) {
+                Ok(plan_info) => plan_info,
+                Err(error) => {
+                    // One missing file must not abort the action on the other groups.
+                    eprintln!(
+                        "{}: {:?} ({error})",
+                        "Skipped".yellow().bold(),
+                        group_info.paths
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        plans.into_iter().flatten().collect()
+    }
+
     fn get_path_info(&self) -> Vec<PathInfo> {
         self.par_iter() // rayon parallel iterator
             .flat_map(|group_info| group_info.flatten())
@@ -295,3 +570,201 @@ impl GroupExtension for [GroupInfo] {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests_group_info {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plan_with_reference_dir() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let archive = dir.path().join("archive");
+        let downloads = dir.path().join("downloads");
+        fs::create_dir_all(&archive)?;
+        fs::create_dir_all(&downloads)?;
+
+        let paths = vec![
+            archive.join("a"),
+            archive.join("b"),
+            downloads.join("c"),
+            downloads.join("d"),
+        ];
+        for path in &paths {
+            fs::write(path, b"same")?;
+        }
+
+        let arguments = Arguments::parse_from([
+            "find-identical-files".as_ref(),
+            "-k".as_ref(),
+            "alphabetical".as_ref(),
+            "--reference_dir".as_ref(),
+            archive.as_os_str(),
+        ]);
+
+        let group_info = GroupInfo {
+            paths: paths.clone(),
+            key: Key::new(4, None)?,
+            num_file: 4,
+            sum_size: 16,
+        };
+
+        // Reference files are kept; only the other files are duplicates.
+        let plans = [group_info.clone()].get_plans(&arguments);
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].keeper, paths[0]);
+        assert_eq!(plans[0].duplicates, paths[2..]);
+        assert!(!group_info.is_reference_only(&arguments));
+
+        // A group with only reference files is hidden and has no plan.
+        let reference_group = GroupInfo {
+            paths: paths[..2].to_vec(),
+            key: Key::new(4, None)?,
+            num_file: 2,
+            sum_size: 8,
+        };
+        assert!(reference_group.is_reference_only(&arguments));
+        assert!(reference_group.get_plan(&arguments)?.is_none());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_reference_symlink_pointing_outside() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let archive = dir.path().join("archive");
+        let downloads = dir.path().join("downloads");
+        fs::create_dir_all(&archive)?;
+        fs::create_dir_all(&downloads)?;
+
+        // A link in the reference directory to a file outside it (--follow_symlinks).
+        fs::write(downloads.join("c"), b"same")?;
+        std::os::unix::fs::symlink(downloads.join("c"), archive.join("link"))?;
+        let paths = vec![archive.join("link"), downloads.join("c")];
+
+        let arguments = Arguments::parse_from([
+            "find-identical-files".as_ref(),
+            "--follow_symlinks".as_ref(),
+            "--reference_dir".as_ref(),
+            archive.as_os_str(),
+        ]);
+
+        let group_info = GroupInfo {
+            paths,
+            key: Key::new(4, None)?,
+            num_file: 2,
+            sum_size: 8,
+        };
+
+        // The link is a reference file: it is never acted on.
+        assert_eq!(group_info.get_references(&arguments), vec![true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plans_skip_group_with_missing_file() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let paths: Vec<PathBuf> = ["a1", "a2", "b1", "b2"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        for path in &paths {
+            fs::write(path, b"same")?;
+        }
+
+        let groups: Vec<GroupInfo> = paths
+            .chunks(2)
+            .map(|chunk| {
+                Ok(GroupInfo {
+                    paths: chunk.to_vec(),
+                    key: Key::new(4, None)?,
+                    num_file: 2,
+                    sum_size: 8,
+                })
+            })
+            .collect::<FIFResult<_>>()?;
+
+        // The keep rule reads the modification times: a removed file fails its group only.
+        fs::remove_file(&paths[1])?;
+        let arguments = Arguments::parse_from(["find-identical-files", "-k", "oldest"]);
+        let plans = groups.get_plans(&arguments);
+
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].duplicates.len(), 1);
+        assert!(paths[2..].contains(&plans[0].keeper));
+        Ok(())
+    }
+
+    #[test]
+    fn test_identical_prefixes() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let mut content = vec![0_u8; 65_536];
+        let paths: Vec<PathBuf> = (1..=3).map(|i| dir.path().join(format!("f{i}"))).collect();
+        fs::write(&paths[0], &content)?;
+        fs::write(&paths[1], &content)?;
+        // Differs after the first 4 KiB.
+        content[10_000] = 1;
+        fs::write(&paths[2], &content)?;
+
+        let arguments = Arguments::parse_from(["find-identical-files"]);
+        let groups = [GroupInfo {
+            paths: paths.clone(),
+            key: Key::new(65_536, None)?,
+            num_file: 3,
+            sum_size: 3 * 65_536,
+        }];
+
+        // The first 4 KiB are identical: the group is kept.
+        let round_1 = groups.get_identical_prefixes(&arguments, 4096)?;
+        assert_eq!(round_1.len(), 1);
+        assert_eq!(round_1[0].num_file, 3);
+
+        // The third file becomes unique and is dropped (16 KiB is a quarter of the files).
+        let round_2 = round_1.get_identical_prefixes(&arguments, 16_384)?;
+        assert_eq!(round_2.len(), 1);
+        assert_eq!(round_2[0].paths.len(), 2);
+        assert!(!round_2[0].paths.contains(&paths[2]));
+
+        // Windows over a quarter of the files are left to the entire file stage.
+        let round_3 = groups.get_identical_prefixes(&arguments, 32_768)?;
+        assert_eq!(round_3[0].paths, paths);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lockstep_same_as_hash() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let contents: [&[u8]; 5] = [b"aaaa", b"bbbb", b"aaaa", b"cccc", b"bbbb"];
+        let paths: Vec<PathBuf> = contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let path = dir.path().join(format!("f{i}"));
+                fs::write(&path, content).map(|_| path)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let arguments = Arguments::parse_from(["find-identical-files"]);
+        let groups = [GroupInfo {
+            paths,
+            key: Key::new(4, None)?,
+            num_file: 5,
+            sum_size: 20,
+        }];
+
+        let mut by_hash = groups.get_identical_files(&arguments, Procedure::EntireFile)?;
+        let mut by_lockstep = groups.get_lockstep_files(&arguments)?;
+        by_hash.sort_identical_files(&arguments);
+        by_lockstep.sort_identical_files(&arguments);
+
+        // Same groups, with the same hashes; the unique file is dropped.
+        assert_eq!(by_lockstep.len(), 2);
+        for (hash, lockstep) in by_hash.iter().zip(&by_lockstep) {
+            assert_eq!(hash.key, lockstep.key);
+            assert_eq!(hash.paths.len(), lockstep.paths.len());
+        }
+        Ok(())
+    }
+}
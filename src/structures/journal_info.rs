@@ -0,0 +1,53 @@
+use crate::{Action, Algorithm, Key};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Journal entry: one step of an action applied to a duplicate.
+///
+/// The journal is a JSON Lines file. Each step is appended twice: once
+/// before the file is changed (`Started`) and once with its final status.
+/// A step without a final status belongs to an interrupted run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Action applied to the duplicate
+    #[serde(rename = "Operation")]
+    pub operation: Action,
+
+    /// The duplicate changed by the action
+    #[serde(rename = "Source")]
+    pub source: PathBuf,
+
+    /// The file kept, or the quarantine path for the quarantine action
+    #[serde(rename = "Target")]
+    pub target: PathBuf,
+
+    /// Key Information (Size and Hash)
+    #[serde(rename = "File information")]
+    pub key: Key,
+
+    /// Algorithm used to calculate the hash
+    #[serde(rename = "Algorithm")]
+    pub algorithm: Algorithm,
+
+    /// Status of the step
+    #[serde(rename = "Status")]
+    pub status: JournalStatus,
+
+    /// Seconds since the UNIX epoch
+    #[serde(rename = "Timestamp")]
+    pub timestamp: u64,
+}
+
+/// Status of a journal step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum JournalStatus {
+    /// Written before the file is changed.
+    Started,
+    /// The action was applied.
+    Done,
+    /// The action was not applied.
+    Skipped,
+    /// The action failed (no file was changed).
+    Failed,
+}
@@ -1,4 +1,4 @@
-use crate::{FIFError, FIFResult, add_thousands_separator};
+use crate::{FIFError, FIFResult, add_thousands_separator, remove_thousands_separator};
 use serde::{Deserialize, Serialize};
 
 /// This key will be used by FileInfo and GroupInfo.
@@ -15,7 +15,8 @@ pub struct Key {
     /// The size of the file in bytes.
     #[serde(
         rename = "Size of individual file",
-        serialize_with = "add_thousands_separator"
+        serialize_with = "add_thousands_separator",
+        deserialize_with = "remove_thousands_separator"
     )]
     pub size: usize,
 }
@@ -0,0 +1,33 @@
+use crate::GroupInfo;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Quarantine manifest entry: a group of identical files and the files moved to quarantine.
+///
+/// The manifest is a JSON array of entries, read back by the restore command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestInfo {
+    /// The group of identical files (paths, size and hash) found by the search
+    #[serde(flatten)]
+    pub group_info: GroupInfo,
+
+    /// The file kept in place (absolute path)
+    #[serde(rename = "Keep")]
+    pub keeper: PathBuf,
+
+    /// The files moved to the quarantine directory
+    #[serde(rename = "Quarantined files")]
+    pub files: Vec<QuarantineFile>,
+}
+
+/// Location of a file before and after being moved to quarantine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineFile {
+    /// Absolute path of the file before being moved
+    #[serde(rename = "Original path")]
+    pub original: PathBuf,
+
+    /// Path of the file inside the quarantine directory
+    #[serde(rename = "Quarantine path")]
+    pub quarantine: PathBuf,
+}
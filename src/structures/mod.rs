@@ -1,7 +1,11 @@
+pub mod action_info;
 pub mod file_info;
 pub mod group_info;
+pub mod journal_info;
 pub mod key_info;
+pub mod manifest_info;
 pub mod path_info;
+pub mod plan_info;
 pub mod total_info;
 
 /*
@@ -9,6 +13,10 @@ Structures defined in this directory:
     Key,
     FileInfo,
     GroupInfo,
+    JournalEntry,
+    ManifestInfo,
     PathInfo,
+    PlanInfo,
+    ActionInfo,
     TotalInfo,
 */
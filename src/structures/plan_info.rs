@@ -0,0 +1,211 @@
+use crate::{
+    Action, ActionInfo, FIFResult, Journal, Key, Outcome, add_thousands_separator, apply_action,
+    args::{Arguments, ResultFormat::*},
+    display_size, link_count, my_print,
+    traits::Colors,
+    write_commands, write_manifest,
+};
+use serde::Serialize;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Describes what an action will do with a group of identical files.
+///
+/// One file is kept (the keeper) and the action is applied to all the others.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanInfo {
+    /// Key Information (Size and Hash)
+    #[serde(rename = "File information")]
+    pub key: Key,
+
+    /// The file that will be kept untouched
+    #[serde(rename = "Keep")]
+    pub keeper: PathBuf,
+
+    /// The files the action will be applied to
+    #[serde(rename = "Duplicates")]
+    pub duplicates: Vec<PathBuf>,
+
+    /// Sum of the sizes of the duplicates
+    #[serde(
+        rename = "Sum of duplicate sizes",
+        serialize_with = "add_thousands_separator"
+    )]
+    pub sum_size: usize,
+}
+
+impl PlanInfo {
+    /// Print PlanInfo fields in the chosen format (JSON, YAML, Personal or Shell)
+    pub fn print_formatted(
+        &self,
+        arguments: &Arguments,
+        action: Action,
+        write: &mut dyn Write,
+    ) -> FIFResult<()> {
+        match &arguments.result_format {
+            Json => {
+                let serialized = serde_json::to_string_pretty(self)?;
+                writeln!(write, "{serialized}\n")?;
+            }
+            Yaml => {
+                let serialized = serde_yaml::to_string(self)?;
+                writeln!(write, "{serialized}")?;
+            }
+            Personal => {
+                writeln!(write, "Hash: {}", self.key.hash.clone().unwrap_or_default())?;
+                writeln!(write, "Keep: {:?}", self.keeper)?;
+                for duplicate in &self.duplicates {
+                    writeln!(write, "{}: {duplicate:?}", action.label())?;
+                }
+                writeln!(
+                    write,
+                    "Sum of duplicate sizes: {}\n",
+                    display_size(self.sum_size, arguments)
+                )?;
+            }
+            Shell => write_commands(self, arguments, action, write)?,
+        }
+
+        Ok(())
+    }
+
+    /// Applies the action to every duplicate, updating the summary.
+    ///
+    /// Errors and skipped files are reported and counted per file, so that
+    /// a single failure does not stop the processing of the remaining files.
+    pub fn apply(
+        &self,
+        arguments: &Arguments,
+        action: Action,
+        journal: &mut Journal,
+        action_info: &mut ActionInfo,
+    ) {
+        for duplicate in &self.duplicates {
+            // Checked before the action: the duplicate may be removed.
+            let frees_space: bool = action == Action::Reflink || frees_space(duplicate);
+
+            match apply_action(
+                arguments,
+                action,
+                &self.keeper,
+                duplicate,
+                &self.key,
+                journal,
+            ) {
+                Ok(Outcome::Done) => {
+                    action_info.num_processed += 1;
+                    if frees_space {
+                        action_info.reclaimed_size += self.key.size;
+                    }
+                }
+                Ok(Outcome::Skipped(reason)) => {
+                    eprintln!("{}: {duplicate:?} ({reason})", "Skipped".yellow().bold());
+                    action_info.num_skipped += 1;
+                }
+                Err(error) => {
+                    eprintln!("{error}");
+                    action_info.num_failed += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if removing or replacing the path frees the space of its content.
+///
+/// A symbolic link, or a file with other hard links (e.g. collapsed with the
+/// duplicate, or outside the search), keeps the content on disk.
+fn frees_space(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .is_ok_and(|metadata| !metadata.is_symlink() && link_count(&metadata) <= 1)
+}
+
+pub trait PlanExtension {
+    /// Print the dry-run preview: the keeper and the duplicates of each group.
+    fn print_preview(&self, arguments: &Arguments, action: Action) -> FIFResult<()>;
+
+    /// Apply the action to all duplicates (only if execution was requested).
+    fn apply_plans(&self, arguments: &Arguments, action: Action) -> FIFResult<ActionInfo>;
+}
+
+impl PlanExtension for [PlanInfo] {
+    fn print_preview(&self, arguments: &Arguments, action: Action) -> FIFResult<()> {
+        // The shell script printed with the identical files is already the preview.
+        if matches!(arguments.result_format, Shell) {
+            return Ok(());
+        }
+
+        let mut buffer: Vec<u8> = Vec::new();
+
+        for plan_info in self {
+            plan_info.print_formatted(arguments, action, &mut buffer)?;
+        }
+
+        my_print(&buffer)
+    }
+
+    fn apply_plans(&self, arguments: &Arguments, action: Action) -> FIFResult<ActionInfo> {
+        let mut action_info = ActionInfo::new(arguments, action);
+        action_info.num_duplicates = self.iter().map(|plan| plan.duplicates.len()).sum();
+        action_info.total_size = self.iter().map(|plan| plan.sum_size).sum();
+
+        if arguments.execute {
+            // The manifest is written before any file is moved.
+            if let (Action::Quarantine, Some(quarantine_dir)) = (action, &arguments.quarantine_dir)
+            {
+                write_manifest(self, quarantine_dir)?;
+            }
+
+            // Every step is recorded in the journal, to be undone later.
+            let mut journal = Journal::open(&arguments.journal)?;
+            eprintln!("Write Journal File: {:?}", journal.path());
+
+            for plan_info in self {
+                plan_info.apply(arguments, action, &mut journal, &mut action_info);
+            }
+        }
+
+        Ok(action_info)
+    }
+}
+
+#[cfg(test)]
+mod tests_plan_info {
+    use super::*;
+    use clap::Parser;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_reclaimed_size_with_hard_links() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        let keeper = dir.path().join("keeper");
+        let duplicate = dir.path().join("duplicate");
+        let unique = dir.path().join("unique");
+        fs::write(&keeper, b"same content")?;
+        fs::write(&duplicate, b"same content")?;
+        fs::write(&unique, b"same content")?;
+        // Another hard link to the duplicate: its content stays on disk.
+        fs::hard_link(&duplicate, dir.path().join("hard_link"))?;
+
+        let mut arguments = Arguments::parse_from(["find-identical-files", "-A", "delete", "-E"]);
+        arguments.journal = dir.path().join("journal.jsonl");
+
+        let plan_info = PlanInfo {
+            key: Key::new(12, None)?,
+            keeper,
+            duplicates: vec![duplicate.clone(), unique.clone()],
+            sum_size: 24,
+        };
+        let action_info = [plan_info].apply_plans(&arguments, Action::Delete)?;
+
+        assert!(!duplicate.exists());
+        assert!(!unique.exists());
+        assert_eq!(action_info.num_processed, 2);
+        assert_eq!(action_info.reclaimed_size, 12);
+        Ok(())
+    }
+}
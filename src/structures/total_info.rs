@@ -1,10 +1,12 @@
 use crate::{
     Algorithm, FIFResult, GroupInfo, add_thousands_separator,
     args::{Arguments, ResultFormat::*},
-    get_thousands_separator, split_and_insert,
+    display_size,
     structures::group_info::GroupExtension,
+    write_comment,
 };
 use serde::Serialize;
+use std::io;
 
 /// Summarize information for all files found in the directory
 #[derive(Debug, Default, Clone, Serialize)]
@@ -12,9 +14,15 @@ pub struct TotalInfo {
     /// Hashing algorithm
     #[serde(rename = "Hashing algorithm")]
     pub algorithm: Algorithm,
+    /// How the contents of the identical files were compared
+    #[serde(rename = "Comparison")]
+    pub comparison: String,
     /// Total number of files found in the directory
     #[serde(rename = "Total number of files")]
     pub total_num_files: usize,
+    /// Paths that are hard links (or followed symbolic links) to a file already found
+    #[serde(rename = "Total number of links")]
+    pub total_num_hard_links: usize,
     /// Total number of identical files
     #[serde(rename = "Total number of identical files")]
     pub total_num_identical: usize,
@@ -35,13 +43,16 @@ impl TotalInfo {
         identical_hash: &[GroupInfo],
         arguments: &Arguments,
         total_num_files: usize,
+        total_num_hard_links: usize,
     ) -> Self {
         let (_result_display, total_info) = rayon::join(
             || -> FIFResult<()> {
                 identical_hash.print_identical_files(arguments)?;
                 Ok(())
             },
-            || -> TotalInfo { identical_hash.get_total_info(arguments, total_num_files) },
+            || -> TotalInfo {
+                identical_hash.get_total_info(arguments, total_num_files, total_num_hard_links)
+            },
         );
 
         total_info
@@ -49,8 +60,6 @@ impl TotalInfo {
 
     /// Print the identicald files information.
     pub fn print_summary(&self, arguments: &Arguments) -> FIFResult<()> {
-        let thousands_separator: char = get_thousands_separator();
-
         match &arguments.result_format {
             Json => {
                 // Serialize TotalInfo to a JSON string.
@@ -64,7 +73,12 @@ impl TotalInfo {
             }
             Personal => {
                 println!("Hashing algorithm: {}", arguments.algorithm); // or self.algorithm
+                println!("Comparison: {}", self.comparison);
                 println!("Total number of files: {}", self.total_num_files);
+                println!(
+                    "Total number of links: {} (same file found by another path, not counted)",
+                    self.total_num_hard_links
+                );
                 println!(
                     "Total number of identical files: {}",
                     self.total_num_identical
@@ -74,9 +88,20 @@ impl TotalInfo {
                     self.total_num_hashes
                 );
                 println!(
-                    "Total size of identical files: {} bytes\n",
-                    split_and_insert(self.total_size, thousands_separator)?
+                    "Total size of identical files: {}\n",
+                    display_size(self.total_size, arguments)
                 );
+                if arguments.is_probabilistic() {
+                    println!(
+                        "Warning: quick mode compares sampled blocks only; \
+                        files may differ outside the samples (use --verify always to confirm).\n"
+                    );
+                }
+            }
+            Shell => {
+                // Keep the script valid: the summary is written as comments.
+                let serialized = serde_yaml::to_string(&self)?;
+                write_comment(&mut io::stdout(), &serialized)?;
             }
         }
         Ok(())
@@ -0,0 +1,125 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parses a time limit used by --newer_than and --older_than.
+///
+/// Accepts a duration before now (`90s`, `45m`, `12h`, `30d` or `2w`)
+/// or a UTC date (`2025-01-31`, `2025-01-31 08:00:00` or `2025-01-31T08:00:00`).
+pub fn parse_time_limit(text: &str) -> Result<SystemTime, String> {
+    let text = text.trim();
+
+    match parse_duration(text) {
+        Some(duration) => SystemTime::now()
+            .checked_sub(duration)
+            .ok_or_else(|| format!("duration too long: {text}")),
+        None => parse_date(text).ok_or_else(|| {
+            format!("expected a duration (e.g. 30d, 12h) or a date (YYYY-MM-DD [HH:MM:SS]): {text}")
+        }),
+    }
+}
+
+/// Parses a number followed by a unit: s, m, h, d or w.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let unit = text.chars().last()?;
+    let number: u64 = text[..text.len() - unit.len_utf8()].parse().ok()?;
+
+    let seconds: u64 = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return None,
+    };
+
+    number.checked_mul(seconds).map(Duration::from_secs)
+}
+
+/// Parses a UTC date, with an optional time of day.
+fn parse_date(text: &str) -> Option<SystemTime> {
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut date_fields = date.splitn(3, '-').map(|field| field.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_fields.next()??,
+        date_fields.next()??,
+        date_fields.next()??,
+    );
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let time_of_day: i64 = match time {
+        Some(time) => {
+            let fields: Vec<i64> = time
+                .split(':')
+                .map(|field| field.parse::<i64>().ok())
+                .collect::<Option<_>>()?;
+            match fields[..] {
+                [hours, minutes] if hours < 24 && minutes < 60 => hours * 3600 + minutes * 60,
+                [hours, minutes, seconds] if hours < 24 && minutes < 60 && seconds < 60 => {
+                    hours * 3600 + minutes * 60 + seconds
+                }
+                _ => return None,
+            }
+        }
+        None => 0,
+    };
+
+    let seconds: i64 = days_from_civil(year, month, day) * 86_400 + time_of_day;
+
+    match u64::try_from(seconds) {
+        Ok(seconds) => UNIX_EPOCH.checked_add(Duration::from_secs(seconds)),
+        Err(_) => UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs())),
+    }
+}
+
+/// Converts a (year, month, day) date to days since 1970-01-01.
+///
+/// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400); // year of era: [0, 399]
+    let mp = (month + 9) % 12; // [0, 11], starting in March
+    let doy = (153 * mp + 2) / 5 + day - 1; // day of year: [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // day of era: [0, 146096]
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests_time_limit {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let at = |seconds: u64| Ok(UNIX_EPOCH + Duration::from_secs(seconds));
+
+        assert_eq!(parse_time_limit("1970-01-02"), at(86_400));
+        assert_eq!(parse_time_limit("2000-03-01"), at(951_868_800));
+        assert_eq!(parse_time_limit("2024-02-29 12:30"), at(1_709_209_800));
+        assert_eq!(parse_time_limit("2024-02-29T12:30:15"), at(1_709_209_815));
+        assert!(parse_time_limit("2024-13-01").is_err());
+        assert!(parse_time_limit("2024-01-01 25:00").is_err());
+        assert!(parse_time_limit("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30d"), Some(Duration::from_secs(2_592_000)));
+        assert_eq!(parse_duration("2w"), Some(Duration::from_secs(1_209_600)));
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("10y"), None);
+
+        let week_ago = parse_time_limit("7d").unwrap_or(UNIX_EPOCH);
+        let elapsed = SystemTime::now()
+            .duration_since(week_ago)
+            .unwrap_or_default();
+        assert!(elapsed >= Duration::from_secs(604_800));
+        assert!(elapsed < Duration::from_secs(604_860));
+    }
+}
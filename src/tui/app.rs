@@ -0,0 +1,352 @@
+use crate::{Action, Arguments, FIFResult, GroupInfo, PlanInfo};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    widgets::{ListState, TableState},
+};
+use std::path::PathBuf;
+
+use super::details::{FileDetails, get_preview};
+
+/// Number of rows moved by PageUp and PageDown.
+const PAGE_SIZE: usize = 10;
+
+/// The list that receives the navigation keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Groups,
+    Files,
+}
+
+/// The screen currently displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Browse,
+    Confirm,
+}
+
+/// State of the interactive review of the identical files.
+pub struct App<'a> {
+    /// Sorted groups of identical files
+    pub groups: &'a [GroupInfo],
+    /// Command line arguments (e.g. --human_readable for the sizes)
+    pub arguments: &'a Arguments,
+    /// Index of the file to keep in each group
+    pub keepers: Vec<usize>,
+    /// Files of each group found in a reference directory (never changed)
+    pub references: Vec<Vec<bool>>,
+    /// Selected group
+    pub group_state: ListState,
+    /// Selected file of the selected group
+    pub file_state: TableState,
+    /// Details of the files of the selected group
+    pub details: Vec<FileDetails>,
+    /// First lines of the selected file
+    pub preview: Vec<String>,
+    pub focus: Focus,
+    pub screen: Screen,
+    /// Action applied to the duplicates after confirmation
+    pub action: Action,
+    /// Actions available (quarantine requires --quarantine_dir)
+    actions: Vec<Action>,
+    /// True when the user confirmed the action
+    pub confirmed: bool,
+    /// True when the review is finished
+    pub quit: bool,
+}
+
+impl<'a> App<'a> {
+    /// Creates the review state, choosing the initial keepers with the keep rule.
+    pub fn new(groups: &'a [GroupInfo], arguments: &'a Arguments) -> FIFResult<Self> {
+        let references: Vec<Vec<bool>> = groups
+            .iter()
+            .map(|group_info| group_info.get_references(arguments))
+            .collect();
+
+        // If the keep rule fails (e.g. a file removed since the search),
+        // the first reference file, or the first file, is kept until the user chooses.
+        let keepers: Vec<usize> = groups
+            .iter()
+            .zip(&references)
+            .map(|(group_info, references)| {
+                group_info
+                    .select_keeper(arguments, references)
+                    .ok()
+                    .flatten()
+                    .or_else(|| references.iter().position(|&reference| reference))
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let actions: Vec<Action> = [
+            Action::Delete,
+            Action::HardLink,
+            Action::Reflink,
+            Action::Symlink,
+            Action::Quarantine,
+        ]
+        .into_iter()
+        .filter(|action| *action != Action::Quarantine || arguments.quarantine_dir.is_some())
+        .collect();
+
+        let mut app = App {
+            groups,
+            arguments,
+            keepers,
+            references,
+            group_state: ListState::default().with_selected(Some(0)),
+            file_state: TableState::default().with_selected(Some(0)),
+            details: Vec::new(),
+            preview: Vec::new(),
+            focus: Focus::Groups,
+            screen: Screen::Browse,
+            action: arguments.action.unwrap_or(Action::Delete),
+            actions,
+            confirmed: false,
+            quit: false,
+        };
+
+        app.load_group();
+        Ok(app)
+    }
+
+    /// Index of the selected group.
+    pub fn group_index(&self) -> usize {
+        self.group_state.selected().unwrap_or_default()
+    }
+
+    /// Index of the selected file.
+    pub fn file_index(&self) -> usize {
+        self.file_state.selected().unwrap_or_default()
+    }
+
+    /// The selected group.
+    pub fn group(&self) -> &GroupInfo {
+        &self.groups[self.group_index()]
+    }
+
+    /// Reads the details of the files of the selected group.
+    fn load_group(&mut self) {
+        self.details = self
+            .group()
+            .paths
+            .iter()
+            .map(|path| FileDetails::new(path))
+            .collect();
+        self.file_state.select(Some(0));
+        self.load_preview();
+    }
+
+    /// Reads the first lines of the selected file.
+    fn load_preview(&mut self) {
+        self.preview = get_preview(&self.group().paths[self.file_index()]);
+    }
+
+    /// Handles a key press.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        match self.screen {
+            Screen::Browse => self.handle_browse_key(key.code),
+            Screen::Confirm => self.handle_confirm_key(key.code),
+        }
+    }
+
+    fn handle_browse_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::BackTab => self.toggle_focus(),
+            KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::Groups,
+            KeyCode::Right | KeyCode::Char('l') => self.focus = Focus::Files,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(PAGE_SIZE as isize),
+            KeyCode::PageUp => self.move_selection(-(PAGE_SIZE as isize)),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            KeyCode::Char(' ') | KeyCode::Enter if self.focus == Focus::Files => self.mark_keeper(),
+            KeyCode::Enter => self.focus = Focus::Files,
+            KeyCode::Char('a') => self.next_action(),
+            KeyCode::Char('x') => self.screen = Screen::Confirm,
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.confirmed = true;
+                self.quit = true;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.screen = Screen::Browse;
+            }
+            _ => {}
+        }
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Groups => Focus::Files,
+            Focus::Files => Focus::Groups,
+        };
+    }
+
+    /// Moves the selection of the focused list by `delta` rows (clamped).
+    fn move_selection(&mut self, delta: isize) {
+        let (current, len) = match self.focus {
+            Focus::Groups => (self.group_index(), self.groups.len()),
+            Focus::Files => (self.file_index(), self.group().paths.len()),
+        };
+
+        let new = current
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+        if new == current {
+            return;
+        }
+
+        match self.focus {
+            Focus::Groups => {
+                self.group_state.select(Some(new));
+                self.load_group();
+            }
+            Focus::Files => {
+                self.file_state.select(Some(new));
+                self.load_preview();
+            }
+        }
+    }
+
+    /// Marks the selected file as the file to keep in its group.
+    ///
+    /// If the group has reference files, only one of them can be marked.
+    fn mark_keeper(&mut self) {
+        let group_index = self.group_index();
+        let references = &self.references[group_index];
+
+        if references.contains(&true) && !references[self.file_index()] {
+            return;
+        }
+
+        self.keepers[group_index] = self.file_index();
+    }
+
+    /// Selects the next available action.
+    fn next_action(&mut self) {
+        let position = self
+            .actions
+            .iter()
+            .position(|action| *action == self.action)
+            .map_or(0, |position| (position + 1) % self.actions.len());
+        self.action = self.actions[position];
+    }
+
+    /// Builds the plans from the files marked to keep.
+    pub fn get_plans(&self) -> Vec<PlanInfo> {
+        self.groups
+            .iter()
+            .zip(&self.keepers)
+            .zip(&self.references)
+            .map(|((group_info, &index), references)| {
+                let duplicates: Vec<PathBuf> = group_info
+                    .paths
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _path)| *i != index && !references[*i])
+                    .map(|(_i, path)| path.clone())
+                    .collect();
+
+                PlanInfo {
+                    key: group_info.key.clone(),
+                    keeper: group_info.paths[index].clone(),
+                    sum_size: group_info.key.size * duplicates.len(),
+                    duplicates,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_app {
+    use super::*;
+    use crate::Key;
+    use clap::Parser;
+
+    fn get_groups() -> Vec<GroupInfo> {
+        (0..3)
+            .map(|i| GroupInfo {
+                paths: vec![
+                    PathBuf::from(format!("/nonexistent/{i}/a")),
+                    PathBuf::from(format!("/nonexistent/{i}/b")),
+                    PathBuf::from(format!("/nonexistent/{i}/c")),
+                ],
+                key: Key {
+                    size: 10,
+                    hash: Some(format!("hash{i}")),
+                },
+                num_file: 3,
+                sum_size: 30,
+            })
+            .collect()
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_navigation_and_keepers() -> FIFResult<()> {
+        let groups = get_groups();
+        let arguments = Arguments::parse_from(["find-identical-files", "-k", "alphabetical"]);
+        let mut app = App::new(&groups, &arguments)?;
+
+        // Move to the last group (clamped) and mark its last file to keep.
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.group_index(), 2);
+
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::End);
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.keepers, vec![0, 0, 2]);
+
+        let plans = app.get_plans();
+        assert_eq!(plans[2].keeper, PathBuf::from("/nonexistent/2/c"));
+        assert_eq!(plans[2].duplicates.len(), 2);
+        assert_eq!(plans[2].sum_size, 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_action_and_confirmation() -> FIFResult<()> {
+        let groups = get_groups();
+        let arguments = Arguments::parse_from([
+            "find-identical-files",
+            "-A",
+            "symlink",
+            "-k",
+            "alphabetical",
+        ]);
+        let mut app = App::new(&groups, &arguments)?;
+
+        // Quarantine is not available without a quarantine directory.
+        assert_eq!(app.action, Action::Symlink);
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.action, Action::Delete);
+
+        // Cancel, then confirm.
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.screen, Screen::Browse);
+        assert!(!app.quit);
+
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.confirmed && app.quit);
+        Ok(())
+    }
+}
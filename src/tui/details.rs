@@ -0,0 +1,183 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::Read,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Number of bytes read to preview a text file (8 KB).
+const PREVIEW_BYTES: u64 = 8 * 1024;
+
+/// Maximum number of lines shown in the preview.
+pub const PREVIEW_LINES: usize = 20;
+
+/// Information displayed for each file of the selected group.
+#[derive(Debug, Clone, Default)]
+pub struct FileDetails {
+    /// Modification time (UTC)
+    pub modified: String,
+    /// Owner of the file (user name or uid)
+    pub owner: String,
+}
+
+impl FileDetails {
+    /// Collects the details of a file, without following symbolic links.
+    pub fn new(path: &Path) -> Self {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) => FileDetails {
+                modified: metadata
+                    .modified()
+                    .map(format_time)
+                    .unwrap_or_else(|_| "-".to_string()),
+                owner: get_owner(&metadata),
+            },
+            Err(_) => FileDetails {
+                modified: "not found".to_string(),
+                owner: "-".to_string(),
+            },
+        }
+    }
+}
+
+/// Formats a time as `YYYY-MM-DD HH:MM:SS` (UTC).
+pub fn format_time(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(error) => -(error.duration().as_secs() as i64),
+    };
+
+    let (days, time_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+///
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097); // day of era: [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365; // year of era: [0, 399]
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // day of year: [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let day = doy - (153 * mp + 2) / 5 + 1; // [1, 31]
+    let month = if mp < 10 { mp + 3 } else { mp - 9 }; // [1, 12]
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Owner of the file: the user name, or the uid if the name is unknown.
+#[cfg(unix)]
+fn get_owner(metadata: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    let uid = metadata.uid();
+    user_name(uid).unwrap_or_else(|| uid.to_string())
+}
+
+/// Owner of the file: not available on this platform.
+#[cfg(not(unix))]
+fn get_owner(_metadata: &Metadata) -> String {
+    "-".to_string()
+}
+
+/// Looks up the user name in the password database.
+#[cfg(target_os = "linux")]
+fn user_name(uid: u32) -> Option<String> {
+    let mut buffer: Vec<libc::c_char> = vec![0; 4096];
+    // SAFETY: passwd is a plain C struct; an all-zero value is valid.
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+
+    // SAFETY: all pointers are valid for the duration of the call, and
+    // buffer.len() is the size of the buffer the strings are written to.
+    let status = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+
+    if status != 0 || result.is_null() {
+        return None;
+    }
+
+    // SAFETY: on success, pw_name points to a NUL-terminated string inside buffer.
+    let name = unsafe { std::ffi::CStr::from_ptr(passwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Looks up the user name: not available on this platform.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+/// Returns the first lines of a text file, or a short note for binary files.
+pub fn get_preview(path: &Path) -> Vec<String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let read = File::open(path).and_then(|file| file.take(PREVIEW_BYTES).read_to_end(&mut bytes));
+
+    if let Err(error) = read {
+        return vec![format!("(cannot read the file: {error})")];
+    }
+
+    if bytes.contains(&0) {
+        return vec!["(binary file)".to_string()];
+    }
+
+    // The last character may have been cut by the preview size.
+    let text = match std::str::from_utf8(&bytes) {
+        Ok(text) => text,
+        Err(error) if error.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return vec!["(binary file)".to_string()],
+    };
+
+    text.lines()
+        .take(PREVIEW_LINES)
+        .map(|line| line.replace('\t', "    "))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_details {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(UNIX_EPOCH), "1970-01-01 00:00:00");
+
+        let time = UNIX_EPOCH + Duration::from_secs(951_827_696); // leap day
+        assert_eq!(format_time(time), "2000-02-29 12:34:56");
+
+        let time = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(format_time(time), "1969-12-31 23:59:59");
+    }
+
+    #[test]
+    fn test_get_preview() -> std::io::Result<()> {
+        let dir = TempDir::new()?;
+
+        let text = dir.path().join("text");
+        fs::write(&text, "first\tline\nsecond line\n")?;
+        assert_eq!(get_preview(&text), vec!["first    line", "second line"]);
+
+        let binary = dir.path().join("binary");
+        fs::write(&binary, [0x7f, b'E', b'L', b'F', 0, 1])?;
+        assert_eq!(get_preview(&binary), vec!["(binary file)"]);
+        Ok(())
+    }
+}
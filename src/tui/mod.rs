@@ -0,0 +1,60 @@
+mod app;
+mod details;
+mod ui;
+
+use crate::{Arguments, FIFResult, GroupInfo, PlanExtension};
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{self, Event, KeyEventKind},
+};
+
+use app::App;
+
+/// Opens the interactive review of the identical files.
+///
+/// The user moves between groups, marks the file to keep in each group and
+/// chooses the action. After confirmation, the action is applied to the duplicates.
+pub fn review_groups(groups: &[GroupInfo], arguments: &Arguments) -> FIFResult<()> {
+    if groups.is_empty() {
+        println!("No identical files found.");
+        return Ok(());
+    }
+
+    let mut app = App::new(groups, arguments)?;
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    result?;
+
+    if !app.confirmed {
+        return Ok(());
+    }
+
+    // The confirmation screen replaces --execute.
+    let mut arguments = arguments.clone();
+    arguments.execute = true;
+
+    let plans = app.get_plans();
+    plans.print_preview(&arguments, app.action)?;
+    plans
+        .apply_plans(&arguments, app.action)?
+        .print_summary(&arguments)?;
+
+    Ok(())
+}
+
+/// Draws the screen and handles the key presses until the user quits.
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> FIFResult<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key);
+        }
+    }
+
+    Ok(())
+}
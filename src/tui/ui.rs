@@ -0,0 +1,244 @@
+use crate::display_size;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, List, ListItem, Paragraph, Row, Table, Wrap},
+};
+
+use super::app::{App, Focus, Screen};
+
+/// Block with a title, highlighted when the list has the focus.
+fn titled_block(title: &str, focused: bool) -> Block<'_> {
+    let style = match focused {
+        true => Style::new().fg(Color::Cyan),
+        false => Style::new().fg(Color::DarkGray),
+    };
+    Block::bordered().title(title).border_style(style)
+}
+
+/// Draws the whole screen.
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let [groups, right] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(body);
+
+    let [files, preview] =
+        Layout::vertical([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(right);
+
+    draw_header(frame, app, header);
+    draw_groups(frame, app, groups);
+    draw_files(frame, app, files);
+    draw_preview(frame, app, preview);
+    draw_footer(frame, app, footer);
+
+    if app.screen == Screen::Confirm {
+        draw_confirmation(frame, app);
+    }
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let header = Line::from(vec![
+        " find-identical-files ".bold().reversed(),
+        format!("  Group {}/{}  ", app.group_index() + 1, app.groups.len()).into(),
+        "Action: ".into(),
+        app.action.to_string().bold().yellow(),
+    ]);
+    frame.render_widget(header, area);
+}
+
+fn draw_groups(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .groups
+        .iter()
+        .map(|group_info| {
+            ListItem::new(format!(
+                "{} × {}",
+                group_info.num_file,
+                display_size(group_info.key.size, app.arguments)
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(titled_block(" Groups ", app.focus == Focus::Groups))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, area, &mut app.group_state);
+}
+
+fn draw_files(frame: &mut Frame, app: &mut App, area: Rect) {
+    let group_info = app.group();
+    let keeper = app.keepers[app.group_index()];
+    let references = &app.references[app.group_index()];
+
+    let rows: Vec<Row> = group_info
+        .paths
+        .iter()
+        .zip(&app.details)
+        .enumerate()
+        .map(|(index, (path, details))| {
+            let (mark, style) = match (index == keeper, references[index]) {
+                (true, _) => ("Keep", Style::new().green().bold()),
+                (false, true) => ("Reference", Style::new().green()),
+                (false, false) => (app.action.label(), Style::new().yellow()),
+            };
+            Row::new(vec![
+                Cell::from(mark).style(style),
+                Cell::from(path.display().to_string()),
+                Cell::from(details.modified.clone()),
+                Cell::from(details.owner.clone()),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        " Files: {} each, hash {} ",
+        display_size(group_info.key.size, app.arguments),
+        group_info.key.hash.as_deref().unwrap_or_default()
+    );
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(13),
+            Constraint::Fill(1),
+            Constraint::Length(19),
+            Constraint::Length(10),
+        ],
+    )
+    .header(Row::new(["Mark", "Path", "Modified (UTC)", "Owner"]).bold())
+    .block(titled_block(&title, app.focus == Focus::Files))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.file_state);
+}
+
+fn draw_preview(frame: &mut Frame, app: &App, area: Rect) {
+    let lines: Vec<Line> = app
+        .preview
+        .iter()
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(titled_block(" Preview ", false))
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let keys = match app.screen {
+        Screen::Browse => {
+            " ↑↓ move  Tab switch list  Space keep file  a change action  x apply  q quit"
+        }
+        Screen::Confirm => " y apply  n cancel",
+    };
+    frame.render_widget(Line::from(keys).dark_gray(), area);
+}
+
+fn draw_confirmation(frame: &mut Frame, app: &App) {
+    let plans = app.get_plans();
+    let num_duplicates: usize = plans.iter().map(|plan| plan.duplicates.len()).sum();
+    let sum_size: usize = plans.iter().map(|plan| plan.sum_size).sum();
+
+    let text = vec![
+        Line::from(vec![
+            "Action: ".into(),
+            app.action.to_string().bold().yellow(),
+        ]),
+        Line::from(format!("Groups: {}", plans.len())),
+        Line::from(format!("Duplicate files: {num_duplicates}")),
+        Line::from(format!(
+            "Size of duplicate files: {}",
+            display_size(sum_size, app.arguments)
+        )),
+        Line::from(""),
+        Line::from("The files marked \"Keep\" are not changed."),
+        Line::from(vec![
+            Span::from("Apply the action? "),
+            "y".bold().green(),
+            "/".into(),
+            "n".bold().red(),
+        ]),
+    ];
+
+    let area = frame
+        .area()
+        .centered(Constraint::Length(50), Constraint::Length(9));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text).block(titled_block(" Confirmation ", true)),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests_ui {
+    use super::*;
+    use crate::{Arguments, FIFResult, GroupInfo, Key};
+    use clap::Parser;
+    use ratatui::{Terminal, backend::TestBackend};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_draw() -> FIFResult<()> {
+        let groups = vec![GroupInfo {
+            paths: vec![
+                PathBuf::from("/nonexistent/a"),
+                PathBuf::from("/nonexistent/b"),
+            ],
+            key: Key {
+                size: 1234,
+                hash: Some("abcdef".to_string()),
+            },
+            num_file: 2,
+            sum_size: 2468,
+        }];
+        let arguments = Arguments::parse_from(["find-identical-files", "-k", "alphabetical"]);
+        let mut app = App::new(&groups, &arguments)?;
+
+        // The test backend never fails.
+        let Ok(mut terminal) = Terminal::new(TestBackend::new(100, 30));
+        let Ok(_) = terminal.draw(|frame| draw(frame, &mut app));
+
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(content.contains("/nonexistent/a"));
+        assert!(content.contains("Keep"));
+        assert!(content.contains("Remove"));
+        assert!(content.contains("bytes each"));
+
+        // Sizes follow --human_readable, as in the other reports.
+        let arguments = Arguments::parse_from(["find-identical-files", "-H"]);
+        let mut app = App::new(&groups, &arguments)?;
+        let Ok(_) = terminal.draw(|frame| draw(frame, &mut app));
+
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(content.contains("1.2 KiB each"));
+        Ok(())
+    }
+}
@@ -1,26 +1,91 @@
-use crate::{Arguments, FIFResult, FileInfo, Key, get_path};
+use crate::{
+    Arguments, FIFResult, FileInfo, IgnoreStack, Key, PathFilter, RootFilter, device_id, file_id,
+    get_paths,
+};
 use jwalk::{DirEntry, Parallelism, WalkDirGeneric};
-use std::path::PathBuf;
+use std::{fs, path::PathBuf, sync::Arc};
 
-/// Collects all files within the specified directory into a single Vector.
+/// Collects all files within the specified directories into a single Vector.
 ///
 /// This function uses `jwalk` for high-performance, multi-threaded directory traversal.
-/// It filters files based on the criteria provided in `Arguments` (size, depth, hidden status).
+/// It filters files based on the criteria provided in `Arguments`
+/// (size, depth, hidden status, glob patterns and ignore files).
 pub fn get_all_files(arguments: &Arguments) -> FIFResult<Vec<FileInfo>> {
-    let path: PathBuf = get_path(arguments)?;
+    let filter = Arc::new(PathFilter::new(arguments)?);
+    let mut all_files: Vec<FileInfo> = Vec::new();
 
-    let min_size: u64 = arguments.min_size;
-    let max_size: u64 = arguments.max_size;
+    for path in get_paths(arguments)? {
+        let root_filter = RootFilter::new(&filter, &path)?;
+        all_files.extend(get_files(arguments, path, root_filter)?);
+    }
+
+    Ok(all_files)
+}
+
+/// State passed from each directory to its children.
+#[derive(Debug, Clone, Default)]
+struct ReadDirState {
+    /// Ignore files of the directory and its parents
+    ignore_stack: IgnoreStack,
+    /// Device and inode numbers of the directory and its parents (--follow_symlinks)
+    ancestors: Vec<(u64, u64)>,
+    /// Device of the input directory (--one_file_system)
+    root_device: Option<u64>,
+}
+
+/// Client state: the state of each directory, and the FileInfo of each entry.
+type ClientState = (ReadDirState, Option<FIFResult<FileInfo>>);
+
+/// Collects all files within a single directory.
+fn get_files(
+    arguments: &Arguments,
+    path: PathBuf,
+    root_filter: RootFilter,
+) -> FIFResult<Vec<FileInfo>> {
+    // The size, modification time and extension filters are applied in the walker threads.
+    let file_filter = Arc::new(arguments.clone());
+    let follow_symlinks: bool = arguments.follow_symlinks;
+
+    let root_device: Option<u64> = match arguments.one_file_system {
+        true => device_id(&fs::metadata(&path)?),
+        false => None,
+    };
+
+    let root_state = ReadDirState {
+        ignore_stack: root_filter.root_stack(),
+        ancestors: Vec::new(),
+        root_device,
+    };
 
     // The client state now holds an Option of a Result.
     // This allows us to bubble up errors that happen inside the parallel threads.
-    let jwalk = WalkDirGeneric::<((), Option<FIFResult<FileInfo>>)>::new(path)
+    // The read_dir state holds the ignore files and the parent directories,
+    // passed from each directory to its children.
+    let jwalk = WalkDirGeneric::<ClientState>::new(path)
         .skip_hidden(arguments.omit_hidden)
         .min_depth(arguments.min_depth)
         .max_depth(arguments.max_depth)
         .parallelism(Parallelism::RayonNewPool(rayon::current_num_threads()))
-        .process_read_dir(move |_depth, _path, _read_dir_state, dir_entry_results| {
-            process_dir_entries(dir_entry_results, min_size, max_size);
+        .follow_links(follow_symlinks)
+        .root_read_dir_state(root_state)
+        .process_read_dir(move |depth, path, read_dir_state, dir_entry_results| {
+            // depth is None when processing the input directory itself (read from its parent).
+            if depth.is_some() {
+                read_dir_state.ignore_stack =
+                    root_filter.read_ignore_files(path, read_dir_state.ignore_stack.take());
+
+                if follow_symlinks
+                    && let Some(dir_id) = fs::metadata(path).ok().and_then(|m| file_id(&m))
+                {
+                    read_dir_state.ancestors.push(dir_id);
+                }
+            }
+            process_dir_entries(
+                dir_entry_results,
+                &root_filter,
+                read_dir_state,
+                &file_filter,
+            );
         });
 
     // Used filter_map(|result| result.ok()) to cleanly discard walking errors
@@ -38,23 +103,45 @@ pub fn get_all_files(arguments: &Arguments) -> FIFResult<Vec<FileInfo>> {
 }
 
 /// Type alias for jwalk results to improve readability.
-type JwalkResults = Vec<Result<DirEntry<((), Option<FIFResult<FileInfo>>)>, jwalk::Error>>;
+type JwalkResults = Vec<Result<DirEntry<ClientState>, jwalk::Error>>;
 
 /// Processes directory entries and populates the client state with either a FileInfo or a FIFError.
-fn process_dir_entries(dir_entry_results: &mut JwalkResults, min_size: u64, max_size: u64) {
+///
+/// Excluded and ignored directories, symbolic links to a parent directory (loops)
+/// and directories on another file system (--one_file_system) are removed,
+/// so that they are never descended into.
+fn process_dir_entries(
+    dir_entry_results: &mut JwalkResults,
+    root_filter: &RootFilter,
+    read_dir_state: &ReadDirState,
+    arguments: &Arguments,
+) {
+    let stack = read_dir_state.ignore_stack.as_deref();
+
+    dir_entry_results.retain(|result| match result {
+        Ok(dir_entry) if dir_entry.file_type().is_dir() => {
+            root_filter.keep_dir(&dir_entry.path(), stack)
+                && !is_loop(dir_entry, &read_dir_state.ancestors)
+                && !is_other_device(dir_entry, read_dir_state.root_device)
+        }
+        _ => true,
+    });
+
     dir_entry_results
         .iter_mut()
         .flatten() // Skip jwalk-specific errors
         .filter(|dir_entry| dir_entry.file_type().is_file())
+        .filter(|dir_entry| root_filter.keep_file(&dir_entry.path(), stack))
         .for_each(|dir_entry| {
             if let Ok(metadata) = dir_entry.metadata() {
                 let file_size = metadata.len();
 
-                if file_size >= min_size && file_size <= max_size {
+                if arguments.file_is_included(&dir_entry.path(), &metadata) {
                     // We attempt to create a Key. If it fails, we store the Err in client_state.
                     let result = Key::new(file_size, None).map(|key| FileInfo {
                         key,
                         path: dir_entry.path(),
+                        file_id: file_id(&metadata),
                     });
 
                     dir_entry.client_state = Some(result);
@@ -62,3 +149,91 @@ fn process_dir_entries(dir_entry_results: &mut JwalkResults, min_size: u64, max_
             }
         });
 }
+
+/// Returns true if the entry is a symbolic link to one of its parent directories.
+///
+/// Directories are compared by their device and inode numbers.
+fn is_loop(dir_entry: &DirEntry<ClientState>, ancestors: &[(u64, u64)]) -> bool {
+    dir_entry.path_is_symlink()
+        && dir_entry
+            .metadata()
+            .ok()
+            .and_then(|metadata| file_id(&metadata))
+            .is_some_and(|dir_id| ancestors.contains(&dir_id))
+}
+
+/// Returns true if the directory is not on the device of the input directory.
+fn is_other_device(dir_entry: &DirEntry<ClientState>, root_device: Option<u64>) -> bool {
+    root_device.is_some()
+        && dir_entry
+            .metadata()
+            .ok()
+            .and_then(|metadata| device_id(&metadata))
+            .is_some_and(|device| Some(device) != root_device)
+}
+
+#[cfg(test)]
+mod tests_with_jwalk {
+    use super::*;
+    use crate::collapse_hard_links;
+    use clap::Parser;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_loop_and_file_link() -> FIFResult<()> {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        fs::create_dir(root.join("sub"))?;
+        fs::write(root.join("file.txt"), b"same content")?;
+        // A link to a parent directory (loop) and a link to a file.
+        symlink("..", root.join("sub/up"))?;
+        symlink("file.txt", root.join("link.txt"))?;
+
+        let arguments = Arguments::parse_from([
+            "find-identical-files".as_ref(),
+            "--follow_symlinks".as_ref(),
+            "-i".as_ref(),
+            root.as_os_str(),
+        ]);
+
+        // The walk terminates, without descending into the loop.
+        let all_files = get_all_files(&arguments)?;
+        assert_eq!(all_files.len(), 2);
+        assert!(
+            all_files
+                .iter()
+                .all(|f| !f.path.starts_with(root.join("sub")))
+        );
+
+        // The link and its target are the same file, not duplicates.
+        let (files, num_hard_links) = collapse_hard_links(all_files);
+        assert_eq!(num_hard_links, 1);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, root.join("file.txt"));
+        Ok(())
+    }
+    #[test]
+    fn test_is_other_device() -> FIFResult<()> {
+        let dir = TempDir::new()?;
+        fs::create_dir(dir.path().join("sub"))?;
+
+        let dir_entry = WalkDirGeneric::<ClientState>::new(dir.path())
+            .min_depth(1)
+            .into_iter()
+            .flatten()
+            .find(|dir_entry| dir_entry.file_type().is_dir())
+            .ok_or_else(|| std::io::Error::other("directory not found"))?;
+
+        // Without --one_file_system, no directory is on another device.
+        assert!(!is_other_device(&dir_entry, None));
+
+        if let Some(device) = device_id(&fs::metadata(dir.path())?) {
+            assert!(!is_other_device(&dir_entry, Some(device)));
+            assert!(is_other_device(&dir_entry, Some(device.wrapping_add(1))));
+        }
+        Ok(())
+    }
+}
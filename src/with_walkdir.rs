@@ -1,6 +1,9 @@
-use crate::{Arguments, FIFError, FIFResult, FileInfo, Key, get_path};
+use crate::{
+    Arguments, FIFError, FIFResult, FileInfo, IgnoreStack, Key, PathFilter, RootFilter, file_id,
+    get_paths,
+};
 use rayon::prelude::*;
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use walkdir::{DirEntry, WalkDir};
 
 /// Collects all files into a single Vector using the `walkdir` crate.
@@ -20,12 +23,16 @@ pub fn get_all_files(arguments: &Arguments) -> FIFResult<Vec<FileInfo>> {
             let metadata = entry.metadata().map_err(|e| FIFError::Io(e.into()))?;
             let file_size: u64 = metadata.len();
 
-            if arguments.size_is_included(file_size) {
+            if arguments.file_is_included(entry.path(), &metadata) {
                 // Key::new returns a FIFResult. If it fails, we propagate the error.
                 let key = Key::new(file_size, None)?;
                 let path = entry.into_path();
 
-                Ok(Some(FileInfo { key, path }))
+                Ok(Some(FileInfo {
+                    key,
+                    path,
+                    file_id: file_id(&metadata),
+                }))
             } else {
                 Ok(None)
             }
@@ -39,20 +46,53 @@ pub fn get_all_files(arguments: &Arguments) -> FIFResult<Vec<FileInfo>> {
     Ok(all_files)
 }
 
-/// Traverses the directory and collects file entries into a Vector.
+/// Traverses the directories and collects file entries into a Vector.
 ///
-/// Filters are applied for hidden files and file types (keeping only regular files).
+/// Filters are applied for hidden files, glob patterns, ignore files
+/// and file types (keeping only regular files).
 fn get_entries(arguments: &Arguments) -> FIFResult<Vec<DirEntry>> {
-    let dir_path: PathBuf = get_path(arguments)?;
+    let filter = Arc::new(PathFilter::new(arguments)?);
 
-    let entries: Vec<DirEntry> = WalkDir::new(dir_path)
-        .min_depth(arguments.min_depth)
-        .max_depth(arguments.max_depth)
+    let root_filters: Vec<(PathBuf, RootFilter)> = get_paths(arguments)?
         .into_iter()
-        // filter_entry stops recursion into hidden directories
-        .filter_entry(|e| !arguments.omit_hidden || !is_hidden(e))
-        .filter_map(|result| result.ok()) // Ignore walking errors (e.g., permission denied)
-        .filter(|entry| entry.file_type().is_file())
+        .map(|dir_path| Ok((dir_path.clone(), RootFilter::new(&filter, &dir_path)?)))
+        .collect::<FIFResult<_>>()?;
+
+    let entries: Vec<DirEntry> = root_filters
+        .into_iter()
+        .flat_map(|(dir_path, root_filter)| {
+            // walkdir keeps no state per directory: cache the ignore files of each one.
+            let mut ignore_stacks: HashMap<PathBuf, IgnoreStack> = HashMap::new();
+
+            // walkdir detects the loops, comparing the device and inode numbers of the parents.
+            WalkDir::new(dir_path)
+                .follow_links(arguments.follow_symlinks)
+                .same_file_system(arguments.one_file_system)
+                .min_depth(arguments.min_depth)
+                .max_depth(arguments.max_depth)
+                .into_iter()
+                // filter_entry stops recursion into hidden, excluded and ignored directories
+                .filter_entry(move |e| {
+                    if arguments.omit_hidden && is_hidden(e) {
+                        return false;
+                    }
+
+                    let stack: IgnoreStack =
+                        match (root_filter.has_ignore_rules(), e.path().parent()) {
+                            (true, Some(parent)) if e.depth() > 0 => {
+                                root_filter.dir_stack(parent, &mut ignore_stacks)
+                            }
+                            _ => None,
+                        };
+
+                    match e.file_type().is_dir() {
+                        true => root_filter.keep_dir(e.path(), stack.as_deref()),
+                        false => root_filter.keep_file(e.path(), stack.as_deref()),
+                    }
+                })
+                .filter_map(|result| result.ok()) // Ignore walking errors (e.g., permission denied)
+                .filter(|entry| entry.file_type().is_file())
+        })
         .collect();
 
     Ok(entries)
@@ -69,3 +109,49 @@ fn is_hidden(entry: &DirEntry) -> bool {
         .map(|s| entry.depth() != 0 && s.starts_with('.'))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests_with_walkdir {
+    use super::*;
+    use crate::collapse_hard_links;
+    use clap::Parser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_loop_and_file_link() -> FIFResult<()> {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        fs::create_dir(root.join("sub"))?;
+        fs::write(root.join("file.txt"), b"same content")?;
+        // A link to a parent directory (loop) and a link to a file.
+        symlink("..", root.join("sub/up"))?;
+        symlink("file.txt", root.join("link.txt"))?;
+
+        let arguments = Arguments::parse_from([
+            "find-identical-files".as_ref(),
+            "--follow_symlinks".as_ref(),
+            "-i".as_ref(),
+            root.as_os_str(),
+        ]);
+
+        // The walk terminates, without descending into the loop.
+        let all_files = get_all_files(&arguments)?;
+        assert_eq!(all_files.len(), 2);
+        assert!(
+            all_files
+                .iter()
+                .all(|f| !f.path.starts_with(root.join("sub")))
+        );
+
+        // The link and its target are the same file, not duplicates.
+        let (files, num_hard_links) = collapse_hard_links(all_files);
+        assert_eq!(num_hard_links, 1);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, root.join("file.txt"));
+        Ok(())
+    }
+}